@@ -1,12 +1,12 @@
-use std::ops::Range;
+use advent_of_code::helpers::range_set::RangeSet;
 
-fn ranges(input: &str) -> impl Iterator<Item = (Range<u32>, Range<u32>)> + '_ {
+fn ranges(input: &str) -> impl Iterator<Item = (RangeSet, RangeSet)> + '_ {
     input.lines().map(|line| {
         let mut iter = line.split(',').map(|r| {
             let mut iter = r.split('-').map(|i| i.parse::<u32>().unwrap());
             let start = iter.next().unwrap();
-            let end = iter.next().unwrap() + 1;
-            Range { start, end }
+            let end = iter.next().unwrap();
+            RangeSet::from(start..=end)
         });
         let r1 = iter.next().unwrap();
         let r2 = iter.next().unwrap();
@@ -15,26 +15,19 @@ fn ranges(input: &str) -> impl Iterator<Item = (Range<u32>, Range<u32>)> + '_ {
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let mut result = 0;
-    ranges(input).for_each(|(r1, r2)| {
-        if (r1.start >= r2.start && r1.end <= r2.end) || (r2.start >= r1.start && r2.end <= r1.end)
-        {
-            result += 1;
-        }
-    });
-
-    Some(result)
+    let result = ranges(input)
+        .filter(|(r1, r2)| r1.is_superset(r2) || r2.is_superset(r1))
+        .count();
+
+    Some(result as u32)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut result = 0;
-    ranges(input).for_each(|(r1, r2)| {
-        if !(r1.start >= r2.end || r2.start >= r1.end) {
-            result += 1;
-        }
-    });
-
-    Some(result)
+    let result = ranges(input)
+        .filter(|(r1, r2)| !r1.intersection(r2).is_empty())
+        .count();
+
+    Some(result as u32)
 }
 
 fn main() {
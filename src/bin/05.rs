@@ -1,4 +1,6 @@
-use std::collections::VecDeque;
+use advent_of_code::helpers::grid::Grid;
+use advent_of_code::helpers::viz::{self, Frame};
+use std::fmt::{self, Display};
 
 fn split_input(input: &str) -> (&str, &str) {
     let mut iter = input.split("\n\n");
@@ -12,7 +14,7 @@ fn parse_state(raw_state: &str) -> State {
     let mut lines_rev_iter = lines.iter().rev();
     let numbers = lines_rev_iter.next().unwrap();
 
-    let mut state = vec![vec![]; (numbers.len() + 1) / 4];
+    let mut state = vec![vec![]; numbers.split_whitespace().count()];
 
     for l in lines_rev_iter {
         let chars = l
@@ -43,11 +45,18 @@ fn parse_moves(raw_moves: &str) -> Vec<(usize, usize, usize)> {
                     None
                 }
             });
-            (
-                numbers.next().unwrap(),
-                numbers.next().unwrap() - 1,
-                numbers.next().unwrap() - 1,
-            )
+            let mut next = || {
+                numbers
+                    .next()
+                    .unwrap_or_else(|| panic!("invalid move: {l}"))
+            };
+            // Stacks are labelled from 1.
+            let (amount, from, to) = (next(), next(), next());
+            let stack = |n: usize| {
+                n.checked_sub(1)
+                    .unwrap_or_else(|| panic!("invalid move: {l}"))
+            };
+            (amount, stack(from), stack(to))
         })
         .collect()
 }
@@ -60,54 +69,120 @@ fn parse_input(input: &str) -> (State, Vec<Move>) {
     (parse_state(raw_state), parse_moves(raw_moves))
 }
 
-fn apply_move(mut state: State, mv: Move) -> State {
-    let (amount, from, to) = mv;
+#[derive(Debug, PartialEq)]
+enum MoveError {
+    UnknownStack(usize),
+    NotEnoughCrates { stack: usize, amount: usize },
+}
 
-    for _ in 0..amount {
-        let from = state.get_mut(from).unwrap();
-        let tmp = from.pop().unwrap();
-        let to = state.get_mut(to).unwrap();
-        to.push(tmp)
+impl Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::UnknownStack(stack) => write!(f, "there is no stack {}", stack + 1),
+            MoveError::NotEnoughCrates { stack, amount } => {
+                write!(f, "stack {} has fewer than {} crates", stack + 1, amount)
+            }
+        }
     }
-    state
 }
 
-fn apply_move2(mut state: State, mv: Move) -> State {
-    let (amount, from, to) = mv;
+trait Crane {
+    /// Puts the crates lifted off a stack, top crate last, into the order they land in.
+    fn arrange(&self, load: &mut Vec<char>);
 
-    let from = state.get_mut(from).unwrap();
-    let mut tmp = VecDeque::new();
-    for _ in 0..amount {
-        tmp.push_front(from.pop().unwrap());
-    }
-    let to = state.get_mut(to).unwrap();
-    for item in tmp {
-        to.push(item);
-    }
+    fn apply(&self, state: &mut State, mv: Move) -> Result<(), MoveError> {
+        let (amount, from, to) = mv;
+        for stack in [from, to] {
+            if stack >= state.len() {
+                return Err(MoveError::UnknownStack(stack));
+            }
+        }
+        let height = state[from].len();
+        if amount > height {
+            return Err(MoveError::NotEnoughCrates {
+                stack: from,
+                amount,
+            });
+        }
+        // one at a time or all at once, crates put back onto their own stack stay as they were.
+        if from == to {
+            return Ok(());
+        }
 
-    state
+        let mut load = state[from].split_off(height - amount);
+        self.arrange(&mut load);
+        state[to].extend(load);
+        Ok(())
+    }
 }
 
-pub fn part_one(input: &str) -> Option<String> {
-    let (mut state, moves) = parse_input(input);
+/// Moves one crate at a time, so the load ends up upside down.
+struct CrateMover9000;
 
-    for mv in moves {
-        state = apply_move(state, mv);
+impl Crane for CrateMover9000 {
+    fn arrange(&self, load: &mut Vec<char>) {
+        load.reverse();
     }
+}
+
+/// Moves all crates at once, keeping their order.
+struct CrateMover9001;
 
-    let result: String = state.iter().filter_map(|stack| stack.last()).collect();
-    Some(result)
+impl Crane for CrateMover9001 {
+    fn arrange(&self, _load: &mut Vec<char>) {}
 }
 
-pub fn part_two(input: &str) -> Option<String> {
+/// The stacks in the puzzle's notation, stack labels included.
+fn render(state: &State) -> String {
+    let height = state.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let mut lines: Vec<String> = (0..height)
+        .rev()
+        .map(|level| {
+            state
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{c}]"),
+                    None => String::from("   "),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    lines.push(
+        (1..=state.len())
+            .map(|label| format!("{label:^3}"))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    lines.join("\n")
+}
+
+fn rearrange(crane: &impl Crane, input: &str) -> String {
     let (mut state, moves) = parse_input(input);
 
-    for mv in moves {
-        state = apply_move2(state, mv);
+    for (i, mv) in moves.into_iter().enumerate() {
+        crane
+            .apply(&mut state, mv)
+            .unwrap_or_else(|err| panic!("invalid move {}: {err}", i + 1));
+        viz::emit(|| {
+            let (amount, from, to) = mv;
+            Frame::new(Grid::parse(&render(&state), |c| c)).caption(format!(
+                "move {amount} from {} to {}",
+                from + 1,
+                to + 1
+            ))
+        });
     }
 
-    let result: String = state.iter().filter_map(|stack| stack.last()).collect();
-    Some(result)
+    state.iter().filter_map(|stack| stack.last()).collect()
+}
+
+pub fn part_one(input: &str) -> Option<String> {
+    Some(rearrange(&CrateMover9000, input))
+}
+
+pub fn part_two(input: &str) -> Option<String> {
+    Some(rearrange(&CrateMover9001, input))
 }
 
 fn main() {
@@ -121,15 +196,82 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_apply_move() {
-        let state = vec![vec!['A', 'B'], vec![]];
-        assert_eq!(apply_move(state, (2, 0, 1)), vec![vec![], vec!['B', 'A']]);
+    fn test_crate_mover_9000() {
+        let mut state = vec![vec!['A', 'B'], vec![]];
+        assert_eq!(CrateMover9000.apply(&mut state, (2, 0, 1)), Ok(()));
+        assert_eq!(state, vec![vec![], vec!['B', 'A']]);
+    }
+
+    #[test]
+    fn test_crate_mover_9001() {
+        let mut state = vec![vec!['A', 'B'], vec![]];
+        assert_eq!(CrateMover9001.apply(&mut state, (2, 0, 1)), Ok(()));
+        assert_eq!(state, vec![vec![], vec!['A', 'B']]);
+    }
+
+    #[test]
+    fn test_same_stack() {
+        let mut state = vec![vec!['A', 'B', 'C']];
+        assert_eq!(CrateMover9000.apply(&mut state, (2, 0, 0)), Ok(()));
+        assert_eq!(CrateMover9001.apply(&mut state, (3, 0, 0)), Ok(()));
+        assert_eq!(state, vec![vec!['A', 'B', 'C']]);
+        assert_eq!(
+            CrateMover9000.apply(&mut state, (4, 0, 0)),
+            Err(MoveError::NotEnoughCrates {
+                stack: 0,
+                amount: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_moves() {
+        let mut state = vec![vec!['A'], vec![]];
+        assert_eq!(
+            CrateMover9000.apply(&mut state, (2, 0, 1)),
+            Err(MoveError::NotEnoughCrates {
+                stack: 0,
+                amount: 2
+            })
+        );
+        assert_eq!(
+            CrateMover9001.apply(&mut state, (1, 1, 0)),
+            Err(MoveError::NotEnoughCrates {
+                stack: 1,
+                amount: 1
+            })
+        );
+        assert_eq!(
+            CrateMover9001.apply(&mut state, (1, 0, 2)),
+            Err(MoveError::UnknownStack(2))
+        );
+        assert_eq!(state, vec![vec!['A'], vec![]]);
+        assert_eq!(
+            MoveError::UnknownStack(2).to_string(),
+            "there is no stack 3"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let input = advent_of_code::read_file("examples", 5);
+        let (raw_state, _raw_moves) = split_input(&input);
+        let mut state = parse_state(raw_state);
+        assert_eq!(render(&state), raw_state);
+
+        CrateMover9000.apply(&mut state, (1, 1, 0)).unwrap();
+        assert_eq!(
+            render(&state),
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+        );
     }
 
     #[test]
-    fn test_apply_move2() {
-        let state = vec![vec!['A', 'B'], vec![]];
-        assert_eq!(apply_move2(state, (2, 0, 1)), vec![vec![], vec!['A', 'B']]);
+    fn test_many_stacks() {
+        let state: State = (0..12).map(|i| vec![(b'A' + i) as char]).collect();
+        let diagram = render(&state);
+        assert!(diagram.ends_with(" 9  10  11  12 "));
+        assert_eq!(parse_state(&diagram), state);
     }
 
     #[test]
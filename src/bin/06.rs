@@ -1,42 +1,124 @@
-use std::collections::{hash_map::Entry, HashMap, VecDeque};
-
-fn find_uniq_sequence(input: &str, length: usize) -> Option<u32> {
-    let mut counts: HashMap<char, usize> = HashMap::new();
-    let mut buffer = VecDeque::new();
-    let result = input.chars().enumerate().find_map(|(i, char)| {
-        buffer.push_back(char);
-        counts
-            .entry(char)
-            .and_modify(|count| *count += 1)
-            .or_insert(1);
-
-        if i > length - 1 {
-            let rem = buffer.pop_front().unwrap();
-            if let Entry::Occupied(o) = counts.entry(rem).and_modify(|count| *count -= 1) {
-                if *o.get() == 0 {
-                    counts.remove(&rem);
-                }
-            };
-        } else {
+use std::fmt::{self, Display};
+use std::io::{self, BufReader, Read};
+
+#[derive(Debug)]
+enum SignalError {
+    InvalidByte { byte: u8, position: usize },
+    Io(io::Error),
+}
+
+impl Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignalError::InvalidByte { byte, position } => {
+                write!(
+                    f,
+                    "{:?} at position {} is not a lowercase letter",
+                    *byte as char, position
+                )
+            }
+            SignalError::Io(err) => write!(f, "could not read signal: {err}"),
+        }
+    }
+}
+
+/// Tracks the last `length` letters of a signal: how often each letter occurs in the window,
+/// and a 26-bit mask of the letters that occur at all.
+struct MarkerDetector {
+    length: usize,
+    window: Vec<u8>,
+    counts: [u32; 26],
+    mask: u32,
+    position: usize,
+}
+
+impl MarkerDetector {
+    /// `None` for a length of zero, there is nothing to detect.
+    fn new(length: usize) -> Option<MarkerDetector> {
+        if length == 0 {
             return None;
         }
+        Some(MarkerDetector {
+            length,
+            window: Vec::with_capacity(length),
+            counts: [0; 26],
+            mask: 0,
+            position: 0,
+        })
+    }
 
-        if counts.len() == length {
-            Some((i + 1) as u32)
+    /// Adds the next byte of the signal. Returns whether the last `length` letters are all
+    /// different, i.e. whether a marker ends here.
+    fn push(&mut self, byte: u8) -> Result<bool, SignalError> {
+        if !byte.is_ascii_lowercase() {
+            return Err(SignalError::InvalidByte {
+                byte,
+                position: self.position,
+            });
+        }
+        let letter = (byte - b'a') as usize;
+
+        let slot = self.position % self.length;
+        if self.window.len() < self.length {
+            self.window.push(letter as u8);
         } else {
-            None
+            let evicted = std::mem::replace(&mut self.window[slot], letter as u8) as usize;
+            self.counts[evicted] -= 1;
+            if self.counts[evicted] == 0 {
+                self.mask &= !(1 << evicted);
+            }
         }
-    });
+        self.counts[letter] += 1;
+        self.mask |= 1 << letter;
+        self.position += 1;
 
-    result
+        Ok(self.mask.count_ones() as usize == self.length)
+    }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    find_uniq_sequence(input, 4)
+/// The positions after which the last `length` letters of the signal were all different.
+/// The signal is read until its end or the first line break. Markers of length zero never
+/// occur.
+fn markers<R: Read>(signal: R, length: usize) -> impl Iterator<Item = Result<usize, SignalError>> {
+    let mut bytes = BufReader::new(signal).bytes();
+    // dropped once the signal ends or turns out to be invalid.
+    let mut detector = MarkerDetector::new(length);
+    std::iter::from_fn(move || {
+        while let Some(current) = detector.as_mut() {
+            let byte = match bytes.next() {
+                Some(Ok(b'\n' | b'\r')) | None => break,
+                Some(Ok(byte)) => byte,
+                Some(Err(err)) => {
+                    detector = None;
+                    return Some(Err(SignalError::Io(err)));
+                }
+            };
+            match current.push(byte) {
+                Ok(true) => return Some(Ok(current.position)),
+                Ok(false) => {}
+                Err(err) => {
+                    detector = None;
+                    return Some(Err(err));
+                }
+            }
+        }
+        detector = None;
+        None
+    })
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    find_uniq_sequence(input, 14)
+fn first_marker(input: &str, length: usize) -> Option<usize> {
+    markers(input.as_bytes(), length)
+        .next()
+        .map(|marker| marker.unwrap_or_else(|err| panic!("invalid input: {err}")))
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    first_marker(input, 4)
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    first_marker(input, 14)
 }
 
 fn main() {
@@ -49,13 +131,68 @@ fn main() {
 mod tests {
     use super::*;
 
+    const EXAMPLES: [(&str, usize, usize); 5] = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    fn all_markers(signal: &str, length: usize) -> Vec<usize> {
+        markers(signal.as_bytes(), length)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
     #[test]
     fn test_part_one() {
-        assert_eq!(part_one("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(7));
+        for (signal, expected, _) in EXAMPLES {
+            assert_eq!(part_one(signal), Some(expected));
+        }
     }
 
     #[test]
     fn test_part_two() {
-        assert_eq!(part_two("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(19));
+        for (signal, _, expected) in EXAMPLES {
+            assert_eq!(part_two(signal), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_all_markers() {
+        assert_eq!(all_markers("abcd", 2), vec![2, 3, 4]);
+        assert_eq!(all_markers("aabaa", 2), vec![3, 4]);
+        assert_eq!(all_markers("abcd", 4), vec![4]);
+        assert_eq!(all_markers("aaaa", 1), vec![1, 2, 3, 4]);
+        assert_eq!(all_markers("abc", 4), vec![]);
+        assert_eq!(all_markers("abc\nxyz", 3), vec![3]);
+        assert_eq!(part_one("abcd\n"), Some(4));
+    }
+
+    #[test]
+    fn test_streaming() {
+        let signal = io::repeat(b'a').take(100_000).chain("bcd".as_bytes());
+        let found: Vec<_> = markers(signal, 4).map(Result::unwrap).collect();
+        assert_eq!(found, vec![100_003]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut found = markers("abcdA".as_bytes(), 4);
+        assert_eq!(found.next().unwrap().unwrap(), 4);
+        let err = found.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'A' at position 4 is not a lowercase letter"
+        );
+        assert!(found.next().is_none());
+    }
+
+    #[test]
+    fn test_zero_length() {
+        assert_eq!(all_markers("abc", 0), vec![]);
+        assert_eq!(first_marker("abc", 0), None);
+        assert_eq!(first_marker("ABC", 0), None);
     }
 }
@@ -1,63 +1,262 @@
-use std::collections::HashMap;
-
-fn get_folder_sizes(input: &str) -> HashMap<String, u32> {
-    let mut cwd: Vec<String> = vec![];
-    let mut folder_sizes: HashMap<String, u32> = HashMap::from([(String::from("/"), 0)]);
-    input.lines().for_each(|line| {
-        match &line.split_whitespace().collect::<Vec<&str>>()[..] {
-            ["$", "cd", "/"] => {
-                cwd.clear();
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+type DirId = usize;
+
+const ROOT: DirId = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Entry {
+    Dir(DirId),
+    File(u64),
+}
+
+#[derive(Debug, Default)]
+struct Dir {
+    name: String,
+    parent: Option<DirId>,
+    entries: BTreeMap<String, Entry>,
+    listed: bool,
+}
+
+/// A transcript line that cannot have come from a real terminal session.
+#[derive(Debug, PartialEq)]
+enum TranscriptError {
+    UnknownDirectory { line: usize, name: String },
+    AboveRoot { line: usize },
+    DuplicateListing { line: usize, path: String },
+    DuplicateEntry { line: usize, name: String },
+    OutputWithoutLs { line: usize },
+    Invalid { line: usize },
+}
+
+impl Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::UnknownDirectory { line, name } => {
+                write!(f, "line {line}: cd into unknown directory {name}")
             }
-            ["$", "cd", ".."] => {
-                cwd.pop();
+            TranscriptError::AboveRoot { line } => write!(f, "line {line}: cd above /"),
+            TranscriptError::DuplicateListing { line, path } => {
+                write!(f, "line {line}: {path} was already listed")
             }
-            ["$", "cd", path] => {
-                cwd.push(format!("{}/{}", cwd.join("/"), path));
+            TranscriptError::DuplicateEntry { line, name } => {
+                write!(f, "line {line}: {name} listed twice")
             }
-            ["$", _, _] => {}
-            ["$", _] => {}
-            ["dir", _] => {}
-            [size, _] => {
-                let size = size.parse::<u32>().unwrap();
-                folder_sizes
-                    .entry(String::from("/"))
-                    .and_modify(|sum| *sum += size);
-                for path in cwd.iter() {
-                    folder_sizes
-                        .entry(path.clone())
-                        .and_modify(|sum| *sum += size)
-                        .or_insert(size);
-                }
+            TranscriptError::OutputWithoutLs { line } => {
+                write!(f, "line {line}: output without a preceding ls")
             }
-            _ => panic!(),
+            TranscriptError::Invalid { line } => write!(f, "line {line}: invalid line"),
+        }
+    }
+}
+
+/// The directory tree as far as the transcript explored it.
+#[derive(Debug)]
+struct FileSystem {
+    dirs: Vec<Dir>,
+}
+
+impl FileSystem {
+    fn parse(input: &str) -> Result<FileSystem, TranscriptError> {
+        let mut fs = FileSystem {
+            dirs: vec![Dir {
+                name: String::from("/"),
+                ..Dir::default()
+            }],
         };
-    });
-    folder_sizes
+        let mut cwd = ROOT;
+        let mut listing = false;
+
+        for (i, text) in input.lines().enumerate() {
+            let line = i + 1;
+            match text.split_whitespace().collect::<Vec<_>>()[..] {
+                ["$", "cd", "/"] => {
+                    cwd = ROOT;
+                    listing = false;
+                }
+                ["$", "cd", ".."] => {
+                    cwd = fs.dirs[cwd]
+                        .parent
+                        .ok_or(TranscriptError::AboveRoot { line })?;
+                    listing = false;
+                }
+                ["$", "cd", name] => {
+                    cwd = match fs.dirs[cwd].entries.get(name) {
+                        Some(Entry::Dir(id)) => *id,
+                        _ => {
+                            return Err(TranscriptError::UnknownDirectory {
+                                line,
+                                name: String::from(name),
+                            })
+                        }
+                    };
+                    listing = false;
+                }
+                ["$", "ls"] => {
+                    if fs.dirs[cwd].listed {
+                        return Err(TranscriptError::DuplicateListing {
+                            line,
+                            path: fs.path(cwd),
+                        });
+                    }
+                    fs.dirs[cwd].listed = true;
+                    listing = true;
+                }
+                [size_or_dir, name] if size_or_dir != "$" => {
+                    if !listing {
+                        return Err(TranscriptError::OutputWithoutLs { line });
+                    }
+                    let entry = match size_or_dir {
+                        "dir" => Entry::Dir(fs.dirs.len()),
+                        size => Entry::File(
+                            size.parse()
+                                .map_err(|_| TranscriptError::Invalid { line })?,
+                        ),
+                    };
+                    if fs.dirs[cwd].entries.contains_key(name) {
+                        return Err(TranscriptError::DuplicateEntry {
+                            line,
+                            name: String::from(name),
+                        });
+                    }
+                    fs.dirs[cwd].entries.insert(String::from(name), entry);
+                    if let Entry::Dir(_) = entry {
+                        fs.dirs.push(Dir {
+                            name: String::from(name),
+                            parent: Some(cwd),
+                            ..Dir::default()
+                        });
+                    }
+                }
+                _ => return Err(TranscriptError::Invalid { line }),
+            }
+        }
+        Ok(fs)
+    }
+
+    fn path(&self, id: DirId) -> String {
+        match self.dirs[id].parent {
+            None => String::from("/"),
+            Some(ROOT) => format!("/{}", self.dirs[id].name),
+            Some(parent) => format!("{}/{}", self.path(parent), self.dirs[id].name),
+        }
+    }
+
+    /// Total size of every directory, indexed by `DirId`.
+    /// Directories are created after their parent, so one backwards pass adds up everything.
+    fn dir_sizes(&self) -> Vec<u64> {
+        let mut sizes: Vec<u64> = self
+            .dirs
+            .iter()
+            .map(|dir| {
+                dir.entries
+                    .values()
+                    .map(|entry| match entry {
+                        Entry::File(size) => *size,
+                        Entry::Dir(_) => 0,
+                    })
+                    .sum()
+            })
+            .collect();
+        for id in (1..self.dirs.len()).rev() {
+            sizes[self.dirs[id].parent.unwrap()] += sizes[id];
+        }
+        sizes
+    }
+
+    /// The smallest directory whose deletion frees at least `bytes`.
+    fn smallest_dir_freeing(&self, bytes: u64) -> Option<(DirId, u64)> {
+        self.dir_sizes()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, size)| *size >= bytes)
+            .min_by_key(|(_, size)| *size)
+    }
+
+    /// The size of the smallest directory that brings free space on a disk of
+    /// `capacity` bytes up to at least `wanted` bytes.
+    fn free_up(&self, capacity: u64, wanted: u64) -> Option<u64> {
+        let free = capacity.checked_sub(self.dir_sizes()[ROOT])?;
+        let (_, size) = self.smallest_dir_freeing(wanted.saturating_sub(free))?;
+        Some(size)
+    }
+
+    /// Like `du`: the size and path of every directory, children before their parent.
+    fn du(&self) -> String {
+        let sizes = self.dir_sizes();
+        let mut out = String::new();
+        self.du_dir(ROOT, &sizes, &mut out);
+        out
+    }
+
+    fn du_dir(&self, id: DirId, sizes: &[u64], out: &mut String) {
+        for entry in self.dirs[id].entries.values() {
+            if let Entry::Dir(child) = entry {
+                self.du_dir(*child, sizes, out);
+            }
+        }
+        out.push_str(&format!("{}\t{}\n", sizes[id], self.path(id)));
+    }
+
+    /// Like `tree`, in the notation of the puzzle description.
+    fn tree(&self) -> String {
+        let mut out = String::from("- / (dir)\n");
+        self.tree_dir(ROOT, 1, &mut out);
+        out
+    }
+
+    fn tree_dir(&self, id: DirId, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        for (name, entry) in &self.dirs[id].entries {
+            match entry {
+                Entry::Dir(child) => {
+                    out.push_str(&format!("{indent}- {name} (dir)\n"));
+                    self.tree_dir(*child, depth + 1, out);
+                }
+                Entry::File(size) => {
+                    out.push_str(&format!("{indent}- {name} (file, size={size})\n"));
+                }
+            }
+        }
+    }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let folder_sizes = get_folder_sizes(input);
+fn parse(input: &str) -> FileSystem {
+    FileSystem::parse(input).unwrap_or_else(|err| panic!("invalid input: {err}"))
+}
 
-    let result: u32 = folder_sizes.into_values().filter(|v| *v < 100000).sum();
+pub fn part_one(input: &str) -> Option<u64> {
+    let result: u64 = parse(input)
+        .dir_sizes()
+        .into_iter()
+        .filter(|v| *v <= 100000)
+        .sum();
     Some(result)
 }
 
-const CAPACITY: u32 = 70000000;
-const SPACE_WANTED: u32 = 30000000;
+const CAPACITY: u64 = 70000000;
+const SPACE_WANTED: u64 = 30000000;
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let folder_sizes = get_folder_sizes(input);
-    let free = CAPACITY - *folder_sizes.get("/").unwrap();
-
-    let mut sizes = folder_sizes.into_values().collect::<Vec<_>>();
-    sizes.sort_unstable();
-    sizes.into_iter().find(|v| free + v > SPACE_WANTED)
+pub fn part_two(input: &str) -> Option<u64> {
+    parse(input).free_up(CAPACITY, SPACE_WANTED)
 }
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 7);
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
+
+    // `cargo solve 07 -- --show tree|du` prints the file system from the transcript.
+    let mut args = pico_args::Arguments::from_env();
+    if let Ok(Some(format)) = args.opt_value_from_str::<_, String>("--show") {
+        let fs = parse(input);
+        match format.as_str() {
+            "du" => print!("{}", fs.du()),
+            "tree" => print!("{}", fs.tree()),
+            _ => eprintln!("unknown format {format}, expected tree or du"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -75,4 +274,76 @@ mod tests {
         let input = advent_of_code::read_file("examples", 7);
         assert_eq!(part_two(&input), Some(24933642));
     }
+
+    #[test]
+    fn test_queries() {
+        let fs = parse(&advent_of_code::read_file("examples", 7));
+        assert_eq!(fs.smallest_dir_freeing(500), Some((3, 584)));
+        assert_eq!(fs.smallest_dir_freeing(1000), Some((1, 94853)));
+        assert_eq!(fs.smallest_dir_freeing(50_000_000), None);
+        assert_eq!(fs.free_up(50_000_000, 1_700_000), Some(94853));
+        assert_eq!(fs.free_up(40_000_000, 1), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let fs = parse(&advent_of_code::read_file("examples", 7));
+        assert_eq!(
+            fs.tree(),
+            "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+        assert_eq!(fs.du(), "584\t/a/e\n94853\t/a\n24933642\t/d\n48381165\t/\n");
+    }
+
+    #[test]
+    fn test_invalid_transcripts() {
+        let errors = [
+            (
+                "$ cd /\n$ cd a",
+                TranscriptError::UnknownDirectory {
+                    line: 2,
+                    name: String::from("a"),
+                },
+            ),
+            ("$ cd ..", TranscriptError::AboveRoot { line: 1 }),
+            (
+                "$ ls\n1 a\n$ ls",
+                TranscriptError::DuplicateListing {
+                    line: 3,
+                    path: String::from("/"),
+                },
+            ),
+            (
+                "$ ls\n1 a\ndir a",
+                TranscriptError::DuplicateEntry {
+                    line: 3,
+                    name: String::from("a"),
+                },
+            ),
+            (
+                "$ ls\ndir a\n$ cd a\n1 b",
+                TranscriptError::OutputWithoutLs { line: 4 },
+            ),
+            ("$ ls\nbig a", TranscriptError::Invalid { line: 2 }),
+            ("$ rm -rf /", TranscriptError::Invalid { line: 1 }),
+        ];
+        for (input, error) in errors {
+            assert_eq!(FileSystem::parse(input).unwrap_err(), error);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use advent_of_code::helpers::viz::{self, Frame};
 use std::collections::HashSet;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -87,10 +88,28 @@ pub fn part_two(input: &str) -> Option<usize> {
         });
 
         visited.insert(knots[9]);
+        viz::emit(|| rope_frame(&knots, &visited));
     });
     Some(visited.len())
 }
 
+fn rope_frame(knots: &[(i32, i32)], visited: &HashSet<(i32, i32)>) -> Frame {
+    let head = knots[0];
+    // later knots are drawn first so that the head ends up on top.
+    let labels = knots.iter().enumerate().rev().map(|(i, pos)| {
+        let label = if i == 0 {
+            'H'
+        } else {
+            char::from_digit(i as u32, 10).unwrap()
+        };
+        (*pos, label)
+    });
+    let cells = visited.iter().map(|pos| (*pos, '#')).chain(labels);
+
+    Frame::sparse(cells.map(|((x, y), c)| ((x as i64, y as i64), c)), '.')
+        .highlight((head.0 as i64, head.1 as i64))
+}
+
 fn main() {
     let input = &advent_of_code::read_file("inputs", 9);
     advent_of_code::solve!(1, part_one, input);
@@ -1,3 +1,7 @@
+use advent_of_code::helpers::grid::Grid;
+use advent_of_code::helpers::ocr;
+use advent_of_code::helpers::viz::{self, Frame};
+
 pub fn part_one(input: &str) -> Option<i32> {
     let values = register_iter(input).enumerate().filter_map(|(i, x)| {
         if i % 40 == 19 {
@@ -10,19 +14,32 @@ pub fn part_one(input: &str) -> Option<i32> {
     Some(values.sum())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    register_iter(input).enumerate().for_each(|(i, x)| {
-        if (x..=(x + 2)).contains(&(((i as i32) % 40) + 1)) {
-            print!("█");
-        } else {
-            print!(" ");
-        };
-        if i % 40 == 39 {
-            println!();
-        }
-    });
+const CRT_WIDTH: usize = 40;
+const CRT_HEIGHT: usize = 6;
+
+fn crt_frame(crt: &Grid<bool>) -> Frame {
+    Frame::new(crt.map(|lit| if *lit { '█' } else { ' ' }))
+}
+
+fn draw_crt(input: &str) -> Grid<bool> {
+    let mut crt = Grid::filled(CRT_WIDTH, CRT_HEIGHT, false);
+    register_iter(input)
+        .take(CRT_WIDTH * CRT_HEIGHT)
+        .enumerate()
+        .for_each(|(i, x)| {
+            let (column, row) = (i % CRT_WIDTH, i / CRT_WIDTH);
+            crt[(column, row)] = (x..=(x + 2)).contains(&((column as i32) + 1));
+            viz::emit(|| {
+                crt_frame(&crt)
+                    .highlight((column as i64, row as i64))
+                    .caption(format!("cycle {}", i + 1))
+            });
+        });
+    crt
+}
 
-    Some(1)
+pub fn part_two(input: &str) -> Option<String> {
+    ocr::recognize(&draw_crt(input))
 }
 
 fn main() {
@@ -65,4 +82,24 @@ mod tests {
         let input = advent_of_code::read_file("examples", 10);
         assert_eq!(part_one(&input), Some(13140));
     }
+
+    #[test]
+    fn test_draw_crt() {
+        let input = advent_of_code::read_file("examples", 10);
+        let expected = "\
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######.....";
+        assert_eq!(draw_crt(&input), Grid::parse(expected, |c| c == '#'));
+    }
+
+    #[test]
+    fn test_part_two() {
+        // the example draws a test pattern instead of letters.
+        let input = advent_of_code::read_file("examples", 10);
+        assert_eq!(part_two(&input), None);
+    }
 }
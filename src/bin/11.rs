@@ -1,4 +1,6 @@
-use std::collections::{HashMap, VecDeque};
+use advent_of_code::helpers::cycle::Detector;
+use advent_of_code::helpers::math::{lcm, Answer, Checked};
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 enum Value {
@@ -29,12 +31,13 @@ impl From<&str> for Value {
 struct Operation {
     a: Value,
     b: Value,
-    op: fn(u64, u64) -> u64,
+    op: fn(Checked<u64>, Checked<u64>) -> Checked<u64>,
 }
 
 impl Operation {
     fn call(&self, old: u64) -> u64 {
-        (self.op)(self.a.const_or(old), self.b.const_or(old))
+        let result = (self.op)(Checked(self.a.const_or(old)), Checked(self.b.const_or(old)));
+        result.0
     }
 }
 
@@ -106,100 +109,276 @@ impl From<&str> for Monkey {
     }
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let blocks = input.split("\n\n");
-    let mut monkeys = blocks.map(Monkey::from).collect::<Vec<_>>();
-    let mut inboxes: HashMap<usize, VecDeque<u64>> = HashMap::new();
-    let mut inspections: HashMap<usize, u64> = HashMap::new();
+/// How worry levels are kept in check after every inspection.
+#[derive(Clone, Copy, Debug)]
+enum Relief {
+    /// Relief that the item was not damaged divides the worry level.
+    Divide(u64),
+    /// Worry levels only matter modulo every monkey's test, so they can be kept below this.
+    Modulo(u64),
+}
 
-    for _round in 0..20 {
-        monkeys = monkeys
-            .into_iter()
-            .enumerate()
-            .map(|(i, mut monkey)| {
-                if let Some(inbox) = inboxes.get_mut(&i) {
-                    monkey.items.append(inbox);
-                }
+impl Relief {
+    fn apply(&self, worry_level: u64) -> u64 {
+        match self {
+            Relief::Divide(divisor) => worry_level / divisor,
+            Relief::Modulo(modulus) => worry_level % modulus,
+        }
+    }
+}
 
-                while let Some(item) = monkey.items.pop_front() {
-                    inspections.entry(i).and_modify(|v| *v += 1).or_insert(1);
-                    let worry_level = monkey.inspect(item);
-                    let worry_level = worry_level / 3;
-                    let throw_to = if worry_level % monkey.modulo == 0 {
-                        monkey.true_index
-                    } else {
-                        monkey.false_index
-                    };
-                    inboxes
-                        .entry(throw_to)
-                        .and_modify(|v| v.push_back(worry_level))
-                        .or_insert_with(|| VecDeque::from([worry_level]));
-                }
+/// An item thrown from one monkey to another.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Hop {
+    round: usize,
+    from: usize,
+    to: usize,
+    worry_level: u64,
+}
+
+#[derive(Debug)]
+struct Troop {
+    monkeys: Vec<Monkey>,
+}
 
-                monkey
-            })
-            .collect::<Vec<_>>();
+impl From<&str> for Troop {
+    fn from(input: &str) -> Troop {
+        Troop {
+            monkeys: input.split("\n\n").map(Monkey::from).collect(),
+        }
     }
+}
 
-    let mut inspection_counts = inspections.into_values().collect::<Vec<_>>();
-    inspection_counts.sort_unstable_by_key(|w| std::cmp::Reverse(*w));
-    let result = inspection_counts.first().unwrap() * inspection_counts.get(1).unwrap();
+impl Troop {
+    fn common_modulus(&self) -> Relief {
+        Relief::Modulo(self.monkeys.iter().map(|m| m.modulo).fold(1, lcm))
+    }
 
-    Some(result)
-}
+    /// Inspects an item and decides who gets it next, returns the target and new worry level.
+    fn inspect(&self, holder: usize, worry_level: u64, relief: Relief) -> (usize, u64) {
+        let monkey = &self.monkeys[holder];
+        let worry_level = relief.apply(monkey.inspect(worry_level));
+        let throw_to = if worry_level.is_multiple_of(monkey.modulo) {
+            monkey.true_index
+        } else {
+            monkey.false_index
+        };
+        (throw_to, worry_level)
+    }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let blocks = input.split("\n\n");
-    let mut monkeys = blocks.map(Monkey::from).collect::<Vec<_>>();
-    let mut inboxes: HashMap<usize, VecDeque<u64>> = HashMap::new();
-    let mut inspections: HashMap<usize, u64> = HashMap::new();
-    let product: u64 = monkeys.iter().map(|m| m.modulo).product();
+    /// Plays `rounds` rounds and returns how many items every monkey inspected in each round.
+    /// Every throw is passed to `on_hop`.
+    fn simulate<F>(&self, rounds: usize, relief: Relief, mut on_hop: F) -> Vec<Vec<u64>>
+    where
+        F: FnMut(Hop),
+    {
+        let mut items: Vec<Vec<u64>> = self
+            .monkeys
+            .iter()
+            .map(|m| m.items.iter().copied().collect())
+            .collect();
+        let mut history = Vec::with_capacity(rounds);
+
+        for round in 0..rounds {
+            let mut inspections = vec![0; self.monkeys.len()];
+            for from in 0..self.monkeys.len() {
+                for item in std::mem::take(&mut items[from]) {
+                    inspections[from] += 1;
+                    let (to, worry_level) = self.inspect(from, item, relief);
+                    items[to].push(worry_level);
+                    on_hop(Hop {
+                        round,
+                        from,
+                        to,
+                        worry_level,
+                    });
+                }
+            }
+            history.push(inspections);
+        }
+        history
+    }
 
-    for _round in 0..10000 {
-        monkeys = monkeys
-            .into_iter()
+    /// Follows a single item through one round, counting inspections per monkey.
+    /// Returns its holder and worry level at the end of the round.
+    fn item_round(&self, state: (usize, u64), relief: Relief, counts: &mut [u64]) -> (usize, u64) {
+        let (mut holder, mut worry_level) = state;
+        loop {
+            counts[holder] += 1;
+            let (throw_to, next) = self.inspect(holder, worry_level, relief);
+            worry_level = next;
+            // monkeys with a lower index already had their turn this round.
+            if throw_to <= holder {
+                return (throw_to, worry_level);
+            }
+            holder = throw_to;
+        }
+    }
+
+    /// Total inspections per monkey after `rounds` rounds, found by following every item on
+    /// its own. Items do not affect each other, so once an item's holder and worry level
+    /// repeat, its inspection counts grow by the same amount every cycle.
+    fn inspections_by_item(&self, rounds: usize, relief: Relief) -> Vec<u64> {
+        let mut totals = vec![0; self.monkeys.len()];
+        let start_states = self
+            .monkeys
+            .iter()
             .enumerate()
-            .map(|(i, mut monkey)| {
-                if let Some(inbox) = inboxes.get_mut(&i) {
-                    monkey.items.append(inbox);
+            .flat_map(|(holder, m)| m.items.iter().map(move |item| (holder, *item)));
+
+        for mut state in start_states {
+            // the item's inspections per monkey before every round.
+            let mut detector = Detector::new();
+            let mut counts = vec![0; self.monkeys.len()];
+            let mut cycle = None;
+            for round in 0..=rounds {
+                cycle = detector.record(state, counts.clone());
+                if cycle.is_some() || round == rounds {
+                    break;
                 }
+                state = self.item_round(state, relief, &mut counts);
+            }
 
-                while let Some(item) = monkey.items.pop_front() {
-                    inspections.entry(i).and_modify(|v| *v += 1).or_insert(1);
-                    let worry_level = monkey.inspect(item);
-                    let worry_level = worry_level % product;
-                    let throw_to = if worry_level % monkey.modulo == 0 {
-                        monkey.true_index
-                    } else {
-                        monkey.false_index
-                    };
-                    inboxes
-                        .entry(throw_to)
-                        .and_modify(|v| v.push_back(worry_level))
-                        .or_insert_with(|| VecDeque::from([worry_level]));
-                }
+            for (monkey, total) in totals.iter_mut().enumerate() {
+                *total += match &cycle {
+                    Some(cycle) => {
+                        let count = |round: usize| detector.values()[round][monkey] as i64;
+                        cycle.extrapolate(rounds, count) as u64
+                    }
+                    None => counts[monkey],
+                };
+            }
+        }
+        totals
+    }
+}
+
+/// The product of the two highest inspection counts, as an `Answer` so that it cannot
+/// wrap around however many rounds are played.
+fn monkey_business(mut inspections: Vec<u64>) -> Answer {
+    inspections.sort_unstable_by_key(|w| std::cmp::Reverse(*w));
+    Answer::from(inspections[0]) * Answer::from(inspections[1])
+}
 
-                monkey
-            })
-            .collect::<Vec<_>>();
+fn total_inspections(history: &[Vec<u64>]) -> Vec<u64> {
+    let mut totals = vec![0; history.first().map_or(0, |round| round.len())];
+    for round in history {
+        for (total, count) in totals.iter_mut().zip(round) {
+            *total += count;
+        }
     }
+    totals
+}
 
-    let mut inspection_counts = inspections.into_values().collect::<Vec<_>>();
-    inspection_counts.sort_unstable_by_key(|w| std::cmp::Reverse(*w));
-    let result = inspection_counts.first().unwrap() * inspection_counts.get(1).unwrap();
+pub fn part_one(input: &str) -> Option<Answer> {
+    let troop = Troop::from(input);
+    let history = troop.simulate(20, Relief::Divide(3), |_| ());
+    Some(monkey_business(total_inspections(&history)))
+}
 
-    Some(result)
+pub fn part_two(input: &str) -> Option<Answer> {
+    let troop = Troop::from(input);
+    let inspections = troop.inspections_by_item(10000, troop.common_modulus());
+    Some(monkey_business(inspections))
 }
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 11);
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
+
+    // `cargo solve 11 -- --trace` prints every throw of part one.
+    let mut args = pico_args::Arguments::from_env();
+    if args.contains("--trace") {
+        Troop::from(input.as_str()).simulate(20, Relief::Divide(3), |hop| {
+            println!(
+                "round {}: monkey {} throws {} to monkey {}",
+                hop.round + 1,
+                hop.from,
+                hop.worry_level,
+                hop.to
+            )
+        });
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::helpers::cycle::brent;
+
+    fn example_troop() -> Troop {
+        Troop::from(advent_of_code::read_file("examples", 11).as_str())
+    }
+
+    #[test]
+    fn test_item_cycle() {
+        let troop = example_troop();
+        let relief = troop.common_modulus();
+        let initial = (0, troop.monkeys[0].items[0]);
+        let step = |state| troop.item_round(state, relief, &mut [0; 4]);
+
+        let cycle = brent(initial, step);
+        let mut state = initial;
+        let mut states = vec![state];
+        for _ in 0..10000 {
+            state = step(state);
+            states.push(state);
+        }
+        assert_eq!(states[cycle.reduce(10000)], states[10000]);
+    }
+
+    #[test]
+    fn test_inspections_by_item() {
+        let troop = example_troop();
+        for (rounds, relief) in [
+            (20, Relief::Divide(3)),
+            (1000, troop.common_modulus()),
+            (10000, troop.common_modulus()),
+        ] {
+            let history = troop.simulate(rounds, relief, |_| ());
+            assert_eq!(
+                troop.inspections_by_item(rounds, relief),
+                total_inspections(&history)
+            );
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let troop = example_troop();
+        let history = troop.simulate(20, troop.common_modulus(), |_| ());
+        assert_eq!(history.len(), 20);
+        assert_eq!(history[0], vec![2, 4, 3, 6]);
+        assert_eq!(total_inspections(&history[..1]), vec![2, 4, 3, 6]);
+        assert_eq!(total_inspections(&history), vec![99, 97, 8, 103]);
+    }
+
+    #[test]
+    fn test_hops() {
+        let troop = example_troop();
+        let mut hops = vec![];
+        troop.simulate(1, Relief::Divide(3), |hop| hops.push(hop));
+        assert_eq!(hops.len(), 14);
+        assert_eq!(
+            hops[0],
+            Hop {
+                round: 0,
+                from: 0,
+                to: 3,
+                worry_level: 500
+            }
+        );
+        assert_eq!(
+            hops[1],
+            Hop {
+                round: 0,
+                from: 0,
+                to: 3,
+                worry_level: 620
+            }
+        );
+    }
 
     #[test]
     fn test_operations() {
@@ -207,6 +386,12 @@ mod tests {
         assert_eq!(Operation::from("old + old").call(3), 6);
     }
 
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn test_operation_overflow() {
+        Operation::from("old * old").call(u64::MAX / 2);
+    }
+
     #[test]
     fn test_monkey() {
         let input = advent_of_code::read_file("examples", 11);
@@ -218,12 +403,12 @@ mod tests {
     #[test]
     fn test_part_one() {
         let input = advent_of_code::read_file("examples", 11);
-        assert_eq!(part_one(&input), Some(10605));
+        assert_eq!(part_one(&input), Some(Answer::from(10605_u64)));
     }
 
     #[test]
     fn test_part_two() {
         let input = advent_of_code::read_file("examples", 11);
-        assert_eq!(part_two(&input), Some(2713310158));
+        assert_eq!(part_two(&input), Some(Answer::from(2713310158_u64)));
     }
 }
@@ -1,128 +1,170 @@
-use pathfinding::prelude::astar;
-use std::collections::HashMap;
+use advent_of_code::helpers::graph::{astar, bfs, manhattan_distance};
+use advent_of_code::helpers::grid::{Grid, Point};
+use std::collections::HashSet;
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct Pos(i32, i32);
+fn parse(input: &str) -> (Point, Point, Grid<char>) {
+    let mut heightmap = Grid::parse(input, |height| height);
 
-impl Pos {
-    fn distance(&self, other: &Pos) -> u32 {
-        (self.0.abs_diff(other.0) + self.1.abs_diff(other.1)) as u32
-    }
+    let start = heightmap.find(|height| *height == 'S').unwrap();
+    let goal = heightmap.find(|height| *height == 'E').unwrap();
+    heightmap[start] = 'a';
+    heightmap[goal] = 'z';
 
-    // fn apply_to_3<F>(f: F) -> i32 where
-    // // The closure takes an `i32` and returns an `i32`.
-    // F: Fn(i32) -> i32 {
-
-    // f(3)
-    // }
-
-    fn successors<F>(&self, heightmap: &HashMap<Pos, char>, check: F) -> Vec<(Pos, u32)>
-    where
-        F: Fn(i32) -> bool,
-    {
-        let current_height = heightmap.get(self).unwrap();
-        let mut successors = vec![];
-        maybe_add(
-            vec![
-                Pos(self.0, self.1 - 1),
-                Pos(self.0 - 1, self.1),
-                Pos(self.0 + 1, self.1),
-                Pos(self.0, self.1 + 1),
-            ],
-            &mut successors,
-            heightmap,
-            current_height,
-            check,
-        );
-        successors
-    }
+    (start, goal, heightmap)
 }
 
-fn maybe_add<F>(
-    positions: Vec<Pos>,
-    acc: &mut Vec<(Pos, u32)>,
-    heightmap: &HashMap<Pos, char>,
-    current_height: &char,
-    check: F,
-) where
-    F: Fn(i32) -> bool,
-{
-    for pos in positions {
-        if let Some(height) = heightmap.get(&pos) {
-            if check((*height as i32) - (*current_height as i32)) {
-                acc.push((pos, 1));
-            }
-        }
-    }
+fn height_diff(from: &char, to: &char) -> i32 {
+    (*to as i32) - (*from as i32)
 }
 
-fn search(search: char) -> impl for<'r, 's> Fn((&'r Pos, &'s char)) -> std::option::Option<Pos> {
-    move |(pos, height): (&Pos, &char)| {
-        if *height == search {
-            Some(pos.clone())
+/// The fewest steps from `start` to `goal` climbing at most one level per step, along with
+/// the squares visited on the way (both ends included).
+fn shortest_path(start: Point, goal: Point, heightmap: &Grid<char>) -> Option<(Vec<Point>, u32)> {
+    astar(
+        heightmap,
+        start,
+        |from, to| (height_diff(from, to) <= 1).then_some(1),
+        |p| manhattan_distance(p, goal) as u32,
+        |p| p == goal,
+    )
+}
+
+/// The path drawn as in the puzzle description: every square shows the direction it is
+/// left in, squares off the path are `.` and the last one is `E`.
+fn render_path(heightmap: &Grid<char>, path: &[Point]) -> String {
+    let mut grid = Grid::filled(heightmap.width(), heightmap.height(), '.');
+    for step in path.windows(2) {
+        let ((x1, y1), (x2, y2)) = (step[0], step[1]);
+        grid[step[0]] = if x2 > x1 {
+            '>'
+        } else if x2 < x1 {
+            '<'
+        } else if y2 > y1 {
+            'v'
         } else {
-            None
-        }
+            '^'
+        };
+    }
+    if let Some(goal) = path.last() {
+        grid[*goal] = 'E';
     }
-}
 
-fn parse(input: &str) -> (Pos, Pos, HashMap<Pos, char>) {
-    let mut heightmap: HashMap<Pos, char> = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars()
-                .enumerate()
-                .map(move |(x, height)| (Pos(x as i32, y as i32), height))
-        })
-        .collect();
-
-    let start = heightmap.iter().find_map(search('S')).unwrap();
-    let goal = heightmap.iter().find_map(search('E')).unwrap();
-    heightmap.insert(start.clone(), 'a');
-    heightmap.insert(goal.clone(), 'z');
+    let mut out = String::new();
+    for y in 0..grid.height() {
+        out.extend((0..grid.width()).map(|x| grid[(x, y)]));
+        out.push('\n');
+    }
+    out
+}
 
-    (start, goal, heightmap)
+/// The heightmap as a plain PPM image, `a` black to `z` white, with the path in red.
+fn to_ppm(heightmap: &Grid<char>, path: &[Point]) -> String {
+    let on_path: HashSet<Point> = path.iter().copied().collect();
+    let mut out = format!("P3\n{} {}\n255\n", heightmap.width(), heightmap.height());
+    for y in 0..heightmap.height() {
+        let pixels: Vec<String> = (0..heightmap.width())
+            .map(|x| {
+                if on_path.contains(&(x, y)) {
+                    "255 0 0".to_string()
+                } else {
+                    let grey = (heightmap[(x, y)] as u32 - 'a' as u32) * 255 / 25;
+                    format!("{grey} {grey} {grey}")
+                }
+            })
+            .collect();
+        out.push_str(&pixels.join(" "));
+        out.push('\n');
+    }
+    out
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
     let (start, goal, heightmap) = parse(input);
-
-    let (_, count) = astar(
-        &start,
-        |p| p.successors(&heightmap, |height_diff| height_diff <= 1),
-        |p| p.distance(&goal) / 3,
-        |p| *p == goal,
-    )
-    .unwrap();
-
+    let (_, count) = shortest_path(start, goal, &heightmap)?;
     Some(count)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let (_, goal, heightmap) = parse(input);
 
-    let (_, count) = astar(
-        &goal,
-        |p| p.successors(&heightmap, |height_diff| height_diff >= -1),
-        |p| p.distance(&goal) / 3,
-        |p| *heightmap.get(p).unwrap() == 'a',
-    )
-    .unwrap();
+    // walk backwards from the goal to the closest lowest square.
+    let (_, count) = bfs(
+        &heightmap,
+        goal,
+        |from, to| height_diff(from, to) >= -1,
+        |p| heightmap[p] == 'a',
+    )?;
 
-    Some(count)
+    Some(count as u32)
 }
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 12);
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
+
+    // `cargo solve 12 -- --show path` prints the part one path, `--export <file>` writes it
+    // on top of the heightmap as a PPM image.
+    let mut args = pico_args::Arguments::from_env();
+    let show = args
+        .opt_value_from_str::<_, String>("--show")
+        .ok()
+        .flatten();
+    let export = args
+        .opt_value_from_str::<_, String>("--export")
+        .ok()
+        .flatten();
+    if show.is_none() && export.is_none() {
+        return;
+    }
+    let (start, goal, heightmap) = parse(input);
+    let Some((path, _)) = shortest_path(start, goal, &heightmap) else {
+        eprintln!("no path to the goal");
+        return;
+    };
+    match show.as_deref() {
+        Some("path") => print!("{}", render_path(&heightmap, &path)),
+        Some(format) => eprintln!("unknown format {format}, expected path"),
+        None => {}
+    }
+    if let Some(file) = export {
+        if let Err(err) = std::fs::write(&file, to_ppm(&heightmap, &path)) {
+            eprintln!("could not write {file}: {err}");
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_render_path() {
+        let (start, goal, heightmap) = parse("Sbcdefghi\nrqponmlkj\nstuvwxyEz");
+        let (path, count) = shortest_path(start, goal, &heightmap).unwrap();
+        assert_eq!(count, 25);
+        assert_eq!(
+            render_path(&heightmap, &path),
+            ">>>>>>>>v\nv<<<<<<<<\n>>>>>>>E.\n"
+        );
+
+        let input = advent_of_code::read_file("examples", 12);
+        let (start, goal, heightmap) = parse(&input);
+        let (path, _) = shortest_path(start, goal, &heightmap).unwrap();
+        let rendered = render_path(&heightmap, &path);
+        assert_eq!(rendered.chars().filter(|c| "><v^".contains(*c)).count(), 31);
+        assert_eq!(rendered.lines().nth(2).unwrap().chars().nth(5), Some('E'));
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let heightmap = Grid::parse("ab\nyz", |c| c);
+        assert_eq!(
+            to_ppm(&heightmap, &[(0, 0), (1, 0)]),
+            "P3\n2 2\n255\n255 0 0 255 0 0\n244 244 244 255 255 255\n"
+        );
+    }
+
     #[test]
     fn test_part_one() {
         let input = advent_of_code::read_file("examples", 12);
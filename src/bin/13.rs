@@ -1,58 +1,185 @@
-use serde_json::{json, Value};
-use std::cmp::Ordering::{self, Equal, Less};
-use std::iter::zip;
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::slice;
+use std::str::FromStr;
 
-fn pairs(input: &str) -> impl Iterator<Item = (Value, Value)> + '_ {
-    input.split("\n\n").map(|lines| {
-        let mut lines = lines.lines();
-        let a: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
-        let b: Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+#[derive(Clone, Debug)]
+enum Packet {
+    Int(u32),
+    List(Vec<Packet>),
+}
 
-        (a, b)
-    })
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    UnexpectedEnd,
+    Unexpected(char, usize),
+    IntTooLarge(usize),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of packet"),
+            ParseError::Unexpected(c, pos) => write!(f, "unexpected '{c}' at position {pos}"),
+            ParseError::IntTooLarge(pos) => write!(f, "integer too large at position {pos}"),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
 }
 
-fn compare(a: &Value, b: &Value) -> Ordering {
-    match (a, b) {
-        (Value::Number(a), Value::Number(b)) => a.as_i64().cmp(&b.as_i64()),
-        (Value::Array(a), Value::Array(b)) => {
-            let len_cmp = a.len().cmp(&b.len());
-            let a_iter = a.iter();
-            let b_iter = b.iter();
-            if let Some(result) = zip(a_iter, b_iter).find_map(|(a, b)| {
-                let result = compare(a, b);
-                if result == Equal {
-                    None
-                } else {
-                    Some(result)
+impl Parser<'_> {
+    fn peek(&self) -> Result<u8, ParseError> {
+        self.bytes
+            .get(self.pos)
+            .copied()
+            .ok_or(ParseError::UnexpectedEnd)
+    }
+
+    fn unexpected(&self) -> ParseError {
+        // Only ASCII has been consumed so far, so `pos` is on a char boundary.
+        let c = self.input[self.pos..].chars().next().unwrap();
+        ParseError::Unexpected(c, self.pos)
+    }
+
+    fn packet(&mut self) -> Result<Packet, ParseError> {
+        match self.peek()? {
+            b'[' => self.list(),
+            b'0'..=b'9' => self.int(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn list(&mut self) -> Result<Packet, ParseError> {
+        self.pos += 1;
+        let mut items = vec![];
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Ok(Packet::List(items));
+        }
+        loop {
+            items.push(self.packet()?);
+            match self.peek()? {
+                b',' => self.pos += 1,
+                b']' => {
+                    self.pos += 1;
+                    return Ok(Packet::List(items));
                 }
-            }) {
-                result
-            } else {
-                len_cmp
+                _ => return Err(self.unexpected()),
             }
         }
-        (Value::Number(a), Value::Array(_)) => compare(&json!([a]), b),
-        (Value::Array(_), Value::Number(b)) => compare(a, &json!([b])),
-        _ => panic!(),
+    }
+
+    fn int(&mut self) -> Result<Packet, ParseError> {
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while let Some(digit) = self.bytes.get(self.pos).filter(|b| b.is_ascii_digit()) {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as u32))
+                .ok_or(ParseError::IntTooLarge(start))?;
+            self.pos += 1;
+        }
+        Ok(Packet::Int(value))
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Packet, ParseError> {
+        let mut parser = Parser {
+            input: s,
+            bytes: s.as_bytes(),
+            pos: 0,
+        };
+        let packet = parser.packet()?;
+        if parser.pos < s.len() {
+            return Err(parser.unexpected());
+        }
+        Ok(packet)
     }
 }
 
+impl Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Int(value) => write!(f, "{value}"),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// The puzzle's ordering. An integer compared to a list is treated as a list holding just
+/// that integer, which `slice::from_ref` provides without allocating.
+impl Ord for Packet {
+    fn cmp(&self, other: &Packet) -> Ordering {
+        match (self, other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            (Packet::List(a), Packet::List(b)) => a.cmp(b),
+            (Packet::Int(_), Packet::List(b)) => slice::from_ref(self).cmp(b),
+            (Packet::List(a), Packet::Int(_)) => a.as_slice().cmp(slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Packet) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Equality in the puzzle's sense, so `2` equals `[[2]]`.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Packet) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+fn parse_packet(line: &str) -> Packet {
+    line.parse()
+        .unwrap_or_else(|err| panic!("invalid packet {line}: {err}"))
+}
+
+fn pairs(input: &str) -> impl Iterator<Item = (Packet, Packet)> + '_ {
+    input.split("\n\n").map(|lines| {
+        let mut lines = lines.lines();
+        let a = parse_packet(lines.next().unwrap());
+        let b = parse_packet(lines.next().unwrap());
+
+        (a, b)
+    })
+}
+
 pub fn part_one(input: &str) -> Option<usize> {
     let sum: usize = pairs(input)
         .enumerate()
-        .map(|(i, (a, b))| if compare(&a, &b) == Less { i + 1 } else { 0 })
+        .map(|(i, (a, b))| if a < b { i + 1 } else { 0 })
         .sum();
     Some(sum)
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    let mut packets = pairs(input).flat_map(|(a, b)| [a, b]).collect::<Vec<_>>();
-    packets.push(json!([[2]]));
-    packets.push(json!([[6]]));
-    packets.sort_by(compare);
-    let index1 = packets.iter().position(|p| *p == json!([[2]])).unwrap() + 1;
-    let index2 = packets.iter().position(|p| *p == json!([[6]])).unwrap() + 1;
+    let packets = pairs(input).flat_map(|(a, b)| [a, b]).collect::<Vec<_>>();
+    let dividers = [parse_packet("[[2]]"), parse_packet("[[6]]")];
+    // The position of a divider in the sorted list is the number of packets before it.
+    let index1 = packets.iter().filter(|p| **p < dividers[0]).count() + 1;
+    let index2 = packets.iter().filter(|p| **p < dividers[1]).count() + 2;
     Some(index1 * index2)
 }
 
@@ -68,16 +195,56 @@ mod tests {
     use super::*;
     use std::cmp::Ordering::{Equal, Greater, Less};
 
+    fn compare(a: &str, b: &str) -> Ordering {
+        parse_packet(a).cmp(&parse_packet(b))
+    }
+
     #[test]
     fn test_compare() {
-        assert_eq!(compare(&json!(0), &json!(1)), Less);
-        assert_eq!(compare(&json!([0]), &json!([1])), Less);
-        assert_eq!(compare(&json!([]), &json!([1])), Less);
-        assert_eq!(compare(&json!([0]), &json!([])), Greater);
-        assert_eq!(compare(&json!([2, 3, 4]), &json!(4)), Less);
-        assert_eq!(compare(&json!([[1], [2, 3, 4]]), &json!([[1], 4])), Less);
-        assert_eq!(compare(&json!(0), &json!(0)), Equal);
-        assert_eq!(compare(&json!([]), &json!([])), Equal);
+        assert_eq!(compare("0", "1"), Less);
+        assert_eq!(compare("[0]", "[1]"), Less);
+        assert_eq!(compare("[]", "[1]"), Less);
+        assert_eq!(compare("[0]", "[]"), Greater);
+        assert_eq!(compare("[2,3,4]", "4"), Less);
+        assert_eq!(compare("[[1],[2,3,4]]", "[[1],4]"), Less);
+        assert_eq!(compare("0", "0"), Equal);
+        assert_eq!(compare("[]", "[]"), Equal);
+        assert_eq!(compare("2", "[[2]]"), Equal);
+    }
+
+    #[test]
+    fn test_display() {
+        let input = advent_of_code::read_file("examples", 13);
+        for line in input.lines().filter(|l| !l.is_empty()) {
+            assert_eq!(parse_packet(line).to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Packet>(), Err(ParseError::UnexpectedEnd));
+        assert_eq!("[1,2".parse::<Packet>(), Err(ParseError::UnexpectedEnd));
+        assert_eq!(
+            "[1,,2]".parse::<Packet>(),
+            Err(ParseError::Unexpected(',', 3))
+        );
+        assert_eq!(
+            "[1] ".parse::<Packet>(),
+            Err(ParseError::Unexpected(' ', 3))
+        );
+        assert_eq!("[é]".parse::<Packet>(), Err(ParseError::Unexpected('é', 1)));
+        assert_eq!(
+            "[-1]".parse::<Packet>(),
+            Err(ParseError::Unexpected('-', 1))
+        );
+        assert_eq!(
+            "[1,99999999999]".parse::<Packet>(),
+            Err(ParseError::IntTooLarge(3))
+        );
+        assert_eq!(
+            ParseError::Unexpected('x', 2).to_string(),
+            "unexpected 'x' at position 2"
+        );
     }
 
     #[test]
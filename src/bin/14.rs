@@ -1,162 +1,185 @@
-use std::{collections::HashMap, ops::RangeInclusive};
+use advent_of_code::helpers::grid::{Grid, Point};
+use advent_of_code::helpers::viz::{self, Frame};
 
+/// Where sand enters the cave, in puzzle coordinates.
+const SOURCE: Pos = Pos(500, 0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Block {
+    Air,
     Rock,
     Sand,
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
-struct Pos(u32, u32);
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+struct Pos(usize, usize);
 impl From<&str> for Pos {
     fn from(coords: &str) -> Pos {
         if let [x, y] = &coords.split(',').collect::<Vec<&str>>()[..] {
             Pos(x.parse().unwrap(), y.parse().unwrap())
         } else {
-            panic!()
+            panic!("invalid position: {coords}")
         }
     }
 }
 
-impl Pos {
-    fn down(&self) -> Pos {
-        Pos(self.0, self.1 + 1)
-    }
-    fn down_left(&self) -> Pos {
-        Pos(self.0 - 1, self.1 + 1)
-    }
-    fn down_right(&self) -> Pos {
-        Pos(self.0 + 1, self.1 + 1)
-    }
+/// What happens to a grain of sand in one step.
+enum Fall {
+    To(Point),
+    Rest,
+    Abyss,
 }
 
-fn parse(
-    input: &str,
-) -> (
-    HashMap<Pos, Block>,
-    RangeInclusive<u32>,
-    RangeInclusive<u32>,
-) {
-    let mut min_x = u32::MAX;
-    let mut max_x = 0;
-    let min_y = 0;
-    let mut max_y = 0;
-    let mut result = HashMap::new();
-
-    let mut assign_rock = |pos| {
-        result.insert(pos, Block::Rock);
-    };
-    let mut widen_ranges = |pos: &Pos| {
-        if pos.0 < min_x {
-            min_x = pos.0;
-        }
-        // if pos.1 < min_y {
-        //     min_y = pos.1;
-        // }
-        if pos.0 > max_x {
-            max_x = pos.0;
-        }
-        if pos.1 > max_y {
-            max_y = pos.1;
-        }
-    };
-
-    input.lines().for_each(|line| {
-        let mut coords = line.split(" -> ").map(Pos::from);
-
-        let mut start: Pos = coords.next().unwrap();
-        widen_ranges(&start);
-        for end in coords {
-            widen_ranges(&end);
-            if start.0 == end.0 {
-                if end.1 > start.1 {
-                    (start.1)..=(end.1)
-                } else {
-                    (end.1)..=(start.1)
-                }
-                .map(|y| Pos(start.0, y))
-                .for_each(&mut assign_rock)
-            } else if start.1 == end.1 {
-                if end.0 > start.0 {
-                    (start.0)..=(end.0)
-                } else {
-                    (end.0)..=(start.0)
-                }
-                .map(|x| Pos(x, start.1))
-                .for_each(&mut assign_rock)
-            } else {
-                panic!()
-            };
-            start = end;
-        }
-    });
+/// The cave around the rock paths and the source. With a floor, it is wide enough for a full
+/// pile of sand to fit on it.
+struct Cave {
+    grid: Grid<Block>,
+    /// The puzzle x coordinate of the leftmost column. A deep cave reaches past x = 0.
+    left: isize,
+    /// The y coordinate of the lowest rock.
+    depth: usize,
+    /// Whether there is a floor two rows below the lowest rock, instead of an abyss.
+    floor: bool,
+}
 
-    (result, min_x..=max_x, min_y..=max_y)
+fn parse(input: &str) -> Vec<Vec<Pos>> {
+    input
+        .lines()
+        .map(|line| line.split(" -> ").map(Pos::from).collect())
+        .collect()
 }
 
-fn advance(pos: &Pos, cave: &HashMap<Pos, Block>) -> Option<Pos> {
-    if cave.contains_key(&pos.down()) {
-        if cave.contains_key(&pos.down_left()) {
-            if cave.contains_key(&pos.down_right()) {
-                None
-            } else {
-                Some(pos.down_right())
+impl Cave {
+    fn new(paths: &[Vec<Pos>], floor: bool) -> Cave {
+        let corners = || paths.iter().flatten();
+        let depth = corners().map(|p| p.1).max().unwrap_or(0);
+        let floor_y = depth + 2;
+        // on a floor, sand piles up at most `floor_y - 1` columns to either side of the source.
+        // Without one, sand that gets past the rocks falls into the abyss. One more column on
+        // each side keeps every grain's neighbours inside the grid.
+        let xs = || corners().map(|p| p.0 as isize);
+        let source_x = SOURCE.0 as isize;
+        let reach = if floor { floor_y as isize } else { 0 };
+        let left = xs().chain([source_x - reach]).min().unwrap() - 1;
+        let right = xs().chain([source_x + reach]).max().unwrap() + 1;
+
+        let mut cave = Cave {
+            grid: Grid::filled((right - left + 1) as usize, floor_y + 1, Block::Air),
+            left,
+            depth,
+            floor,
+        };
+        for path in paths {
+            for line in path.windows(2) {
+                let (Pos(x1, y1), Pos(x2, y2)) = (line[0], line[1]);
+                assert!(x1 == x2 || y1 == y2, "diagonal rock path");
+                for y in y1.min(y2)..=y1.max(y2) {
+                    for x in x1.min(x2)..=x1.max(x2) {
+                        let point = cave.point(Pos(x, y));
+                        cave.grid[point] = Block::Rock;
+                    }
+                }
+            }
+            if let [pos] = path[..] {
+                let point = cave.point(pos);
+                cave.grid[point] = Block::Rock;
             }
-        } else {
-            Some(pos.down_left())
         }
-    } else {
-        Some(pos.down())
-    }
-}
-
-pub fn part_one(input: &str) -> Option<u32> {
-    let (mut cave, rx, ry) = parse(input);
-    let mut sand_count = 0;
-
-    'outer: loop {
-        sand_count += 1;
-        let mut pos = Pos(500, 0);
-        'inner: loop {
-            if !rx.contains(&pos.0) || !ry.contains(&pos.1) {
-                break 'outer;
-            };
-
-            if let Some(new_pos) = advance(&pos, &cave) {
-                pos = new_pos
-            } else {
-                cave.insert(pos, Block::Sand);
-                break 'inner;
+        if floor {
+            for x in 0..cave.grid.width() {
+                cave.grid[(x, floor_y)] = Block::Rock;
             }
         }
+        cave
     }
-    Some(sand_count - 1)
-}
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let (mut cave, _rx, ry) = parse(input);
-    let mut sand_count = 0;
+    /// The grid position of a puzzle position.
+    fn point(&self, Pos(x, y): Pos) -> Point {
+        ((x as isize - self.left) as usize, y)
+    }
+
+    fn source(&self) -> Point {
+        self.point(SOURCE)
+    }
 
-    'outer: loop {
-        sand_count += 1;
-        let mut pos = Pos(500, 0);
-        if cave.contains_key(&pos) {
-            break 'outer;
+    /// Without a floor, sand below the lowest rock falls forever. Either way it never comes to
+    /// rest in the outermost columns, so it cannot fall off the sides of the grid.
+    fn fall(&self, (x, y): Point) -> Fall {
+        if !self.floor && y >= self.depth {
+            return Fall::Abyss;
         }
-        'inner: loop {
-            if pos.1 == ry.end() + 1 {
-                cave.insert(pos, Block::Sand);
-                break 'inner;
+        for next in [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)] {
+            if self.grid[next] == Block::Air {
+                return Fall::To(next);
             }
+        }
+        Fall::Rest
+    }
 
-            if let Some(new_pos) = advance(&pos, &cave) {
-                pos = new_pos
-            } else {
-                cave.insert(pos, Block::Sand);
-                break 'inner;
+    /// Pours sand until it runs into the abyss or blocks the source, returns the number of
+    /// grains that came to rest. Every grain follows the one before it until the cell that
+    /// one settled in, so the path there is kept and the next grain starts from its end.
+    fn pour(&mut self) -> usize {
+        let mut path = vec![self.source()];
+        let mut grains = 0;
+        while let Some(&pos) = path.last() {
+            match self.fall(pos) {
+                Fall::To(next) => path.push(next),
+                Fall::Abyss => break,
+                Fall::Rest => {
+                    self.grid[pos] = Block::Sand;
+                    path.pop();
+                    grains += 1;
+                    viz::emit(|| {
+                        Frame::new(Grid::parse(&self.render(), |c| c))
+                            .caption(format!("{grains} grains"))
+                    });
+                }
             }
         }
+        grains
+    }
+
+    /// The cave as drawn in the puzzle description, cropped to the rock and sand.
+    fn render(&self) -> String {
+        let floor_y = self.depth + 2;
+        let filled: Vec<Point> = self
+            .grid
+            .iter()
+            .filter(|(pos, block)| **block != Block::Air && pos.1 < floor_y)
+            .map(|(pos, _)| pos)
+            .chain([self.source()])
+            .collect();
+        let min_x = filled.iter().map(|p| p.0).min().unwrap();
+        let max_x = filled.iter().map(|p| p.0).max().unwrap();
+        let max_y = if self.floor {
+            floor_y
+        } else {
+            filled.iter().map(|p| p.1).max().unwrap()
+        };
+
+        let mut out = String::new();
+        for y in 0..=max_y {
+            for x in min_x..=max_x {
+                out.push(match self.grid[(x, y)] {
+                    Block::Rock => '#',
+                    Block::Sand => 'o',
+                    Block::Air if (x, y) == self.source() => '+',
+                    Block::Air => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
     }
-    Some(sand_count - 1)
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    Some(Cave::new(&parse(input), false).pour())
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    Some(Cave::new(&parse(input), true).pour())
 }
 
 fn main() {
@@ -171,20 +194,90 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let (parsed, rx, ry) = parse("5,0 -> 7,0 -> 7,2 -> 9,2 -> 9,0 -> 8,0");
-        assert_eq!(parsed.len(), 10);
-        assert!(parsed.contains_key(&Pos(5, 0)));
-        assert!(parsed.contains_key(&Pos(6, 0)));
-        assert!(parsed.contains_key(&Pos(7, 0)));
-        assert!(parsed.contains_key(&Pos(7, 1)));
-        assert!(parsed.contains_key(&Pos(7, 2)));
-        assert!(parsed.contains_key(&Pos(8, 2)));
-        assert!(parsed.contains_key(&Pos(9, 2)));
-        assert!(parsed.contains_key(&Pos(9, 1)));
-        assert!(parsed.contains_key(&Pos(9, 0)));
-        assert!(parsed.contains_key(&Pos(8, 0)));
-        assert_eq!(rx, 5..=9);
-        assert_eq!(ry, 0..=2);
+        let paths = parse("5,0 -> 7,0 -> 7,2 -> 9,2 -> 9,0 -> 8,0");
+        let cave = Cave::new(&paths, false);
+        assert_eq!((cave.left, cave.depth), (4, 2));
+        let rocks = cave.grid.iter().filter(|(_, b)| **b == Block::Rock);
+        assert_eq!(rocks.count(), 10);
+        for (x, y) in [(5, 0), (6, 0), (7, 1), (8, 2), (9, 1), (8, 0)] {
+            assert_eq!(cave.grid[cave.point(Pos(x, y))], Block::Rock);
+        }
+        assert_eq!(cave.grid[cave.point(Pos(8, 1))], Block::Air);
+
+        // only the floor needs room for a pile around the source.
+        let input = advent_of_code::read_file("examples", 14);
+        assert_eq!(Cave::new(&parse(&input), false).grid.width(), 12);
+        assert_eq!(Cave::new(&parse(&input), true).grid.width(), 25);
+    }
+
+    #[test]
+    fn test_deep_cave() {
+        // the floor is so deep that the pile spreads past x = 0.
+        let paths = parse("499,600 -> 501,600");
+        assert_eq!(Cave::new(&paths, true).left, 500 - 602 - 1);
+        // a full triangle of 602 rows, without the rocks and the cell they shelter.
+        assert_eq!(Cave::new(&paths, true).pour(), 602 * 602 - 3 - 1);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = advent_of_code::read_file("examples", 14);
+        let mut cave = Cave::new(&parse(&input), false);
+        assert_eq!(
+            cave.render(),
+            "\
+......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+"
+        );
+        cave.pour();
+        assert_eq!(
+            cave.render(),
+            "\
+......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+"
+        );
+    }
+
+    #[test]
+    fn test_render_floor() {
+        let input = advent_of_code::read_file("examples", 14);
+        let mut cave = Cave::new(&parse(&input), true);
+        cave.pour();
+        assert_eq!(
+            cave.render(),
+            "\
+..........o..........
+.........ooo.........
+........ooooo........
+.......ooooooo.......
+......oo#ooo##o......
+.....ooo#ooo#ooo.....
+....oo###ooo#oooo....
+...oooo.oooo#ooooo...
+..oooooooooo#oooooo..
+.ooo#########ooooooo.
+ooooo.......ooooooooo
+#####################
+"
+        );
     }
 
     #[test]
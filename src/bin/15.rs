@@ -0,0 +1,164 @@
+use advent_of_code::helpers::range_set::RangeSet;
+use regex::Regex;
+
+type Pos = (i64, i64);
+
+fn distance(a: Pos, b: Pos) -> i64 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+#[derive(Debug)]
+struct Sensor {
+    pos: Pos,
+    beacon: Pos,
+    radius: i64,
+}
+
+impl Sensor {
+    fn covers(&self, pos: Pos) -> bool {
+        distance(self.pos, pos) <= self.radius
+    }
+
+    /// The x coordinates on row `y` that are at most `radius` away.
+    fn coverage(&self, y: i64) -> RangeSet {
+        let reach = self.radius - (self.pos.1 - y).abs();
+        RangeSet::from(self.pos.0 - reach..self.pos.0 + reach + 1)
+    }
+
+    /// The coefficients of the four lines just outside the sensor's range, as
+    /// `(x + y = a)` and `(x - y = b)` lines.
+    fn boundary(&self) -> ([i64; 2], [i64; 2]) {
+        let (x, y) = self.pos;
+        let r = self.radius + 1;
+        ([x + y - r, x + y + r], [x - y - r, x - y + r])
+    }
+}
+
+fn parse(input: &str) -> Vec<Sensor> {
+    let re = Regex::new(r"-?\d+").unwrap();
+    input
+        .lines()
+        .map(|line| {
+            let numbers: Vec<i64> = re
+                .find_iter(line)
+                .map(|m| m.as_str().parse().unwrap())
+                .collect();
+            match numbers[..] {
+                [sx, sy, bx, by] => Sensor {
+                    pos: (sx, sy),
+                    beacon: (bx, by),
+                    radius: distance((sx, sy), (bx, by)),
+                },
+                _ => panic!("invalid sensor: {line}"),
+            }
+        })
+        .collect()
+}
+
+/// Number of positions on row `y` that cannot contain a beacon.
+fn excluded_on_row(sensors: &[Sensor], y: i64) -> u64 {
+    let covered: RangeSet = sensors.iter().map(|s| s.coverage(y)).collect();
+    let beacons: RangeSet = sensors
+        .iter()
+        .filter(|s| s.beacon.1 == y)
+        .map(|s| RangeSet::from(s.beacon.0..=s.beacon.0))
+        .collect();
+    covered.difference(&beacons).len()
+}
+
+/// The only position in `0..=max` on both axes not covered by any sensor.
+/// If there is just one, it lies right outside the range of several sensors, so it is on an
+/// intersection of their boundary lines, or where one of them meets the edge of the area.
+fn distress_beacon(sensors: &[Sensor], max: i64) -> Option<Pos> {
+    let (mut a_lines, mut b_lines): (Vec<i64>, Vec<i64>) = (vec![], vec![]);
+    for sensor in sensors {
+        let (a, b) = sensor.boundary();
+        a_lines.extend(a);
+        b_lines.extend(b);
+    }
+    for lines in [&mut a_lines, &mut b_lines] {
+        lines.sort_unstable();
+        lines.dedup();
+    }
+
+    let crossings = a_lines.iter().flat_map(|a| {
+        b_lines
+            .iter()
+            .filter(move |b| (a + *b) % 2 == 0)
+            .map(move |b| ((a + b) / 2, (a - b) / 2))
+    });
+    let edges = a_lines
+        .iter()
+        .flat_map(|a| [(0, *a), (max, a - max), (*a, 0), (a - max, max)])
+        .chain(
+            b_lines
+                .iter()
+                .flat_map(|b| [(0, -b), (max, max - b), (*b, 0), (b + max, max)]),
+        );
+    let corners = [(0, 0), (0, max), (max, 0), (max, max)];
+
+    crossings
+        .chain(edges)
+        .chain(corners)
+        .filter(|(x, y)| (0..=max).contains(x) && (0..=max).contains(y))
+        .find(|pos| sensors.iter().all(|s| !s.covers(*pos)))
+}
+
+fn tuning_frequency((x, y): Pos) -> i64 {
+    x * 4_000_000 + y
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    Some(excluded_on_row(&parse(input), 2_000_000))
+}
+
+pub fn part_two(input: &str) -> Option<i64> {
+    distress_beacon(&parse(input), 4_000_000).map(tuning_frequency)
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 15);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example asks about row 10 and a 0..=20 area instead of the real input's values.
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 15);
+        assert_eq!(excluded_on_row(&parse(&input), 10), 26);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 15);
+        let beacon = distress_beacon(&parse(&input), 20);
+        assert_eq!(beacon, Some((14, 11)));
+        assert_eq!(beacon.map(tuning_frequency), Some(56000011));
+    }
+
+    #[test]
+    fn test_sensor() {
+        let sensor = &parse("Sensor at x=8, y=7: closest beacon is at x=2, y=10")[0];
+        assert_eq!(sensor.radius, 9);
+        assert_eq!(sensor.coverage(10), RangeSet::from(2..=14));
+        assert_eq!(sensor.coverage(16), RangeSet::from(8..=8));
+        assert!(sensor.coverage(17).is_empty());
+        assert!(sensor.covers((8, -2)));
+        assert!(!sensor.covers((8, -3)));
+    }
+
+    #[test]
+    fn test_beacon_on_edge() {
+        // covers everything in 0..=2 but (0, 2).
+        let sensor = &parse("Sensor at x=2, y=0: closest beacon is at x=1, y=2")[0];
+        assert_eq!(
+            distress_beacon(std::slice::from_ref(sensor), 2),
+            Some((0, 2))
+        );
+    }
+}
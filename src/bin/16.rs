@@ -0,0 +1,159 @@
+use advent_of_code::helpers::graph::floyd_warshall;
+use regex::Regex;
+use std::collections::HashMap;
+
+const START: &str = "AA";
+
+/// The valves worth opening, with travel times between them already worked out.
+/// Index `i` of `flows` is bit `i` of the subset masks, the start is not one of them.
+#[derive(Debug)]
+struct Network {
+    flows: Vec<u32>,
+    /// Minutes from the start to every valve.
+    from_start: Vec<u32>,
+    /// Minutes between every pair of valves.
+    distances: Vec<Vec<u32>>,
+}
+
+fn parse(input: &str) -> Network {
+    let names_re = Regex::new(r"[A-Z]{2}").unwrap();
+    let flow_re = Regex::new(r"\d+").unwrap();
+
+    let valves: Vec<(&str, u32, Vec<&str>)> = input
+        .lines()
+        .map(|line| {
+            let mut names = names_re.find_iter(line).map(|m| m.as_str());
+            let name = names.next().unwrap();
+            let flow = flow_re.find(line).unwrap().as_str().parse().unwrap();
+            (name, flow, names.collect())
+        })
+        .collect();
+    let index: HashMap<&str, usize> = valves
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _, _))| (*name, i))
+        .collect();
+
+    let index = &index;
+    let edges = valves
+        .iter()
+        .enumerate()
+        .flat_map(|(from, (_, _, tunnels))| tunnels.iter().map(move |to| (from, index[to], 1)));
+    let costs = floyd_warshall(valves.len(), edges);
+    let distance = |from: usize, to: usize| {
+        costs[(from, to)]
+            .unwrap_or_else(|| panic!("no tunnel from {} to {}", valves[from].0, valves[to].0))
+    };
+
+    let useful: Vec<usize> = (0..valves.len()).filter(|i| valves[*i].1 > 0).collect();
+    assert!(useful.len() <= 16, "too many valves with a flow rate");
+    let start = *index.get(START).expect("no start valve");
+
+    Network {
+        flows: useful.iter().map(|i| valves[*i].1).collect(),
+        from_start: useful.iter().map(|to| distance(start, *to)).collect(),
+        distances: useful
+            .iter()
+            .map(|from| useful.iter().map(|to| distance(*from, *to)).collect())
+            .collect(),
+    }
+}
+
+impl Network {
+    /// The most pressure that can be released in `minutes` by opening exactly the valves in
+    /// each subset, indexed by subset mask.
+    fn best_per_subset(&self, minutes: u32) -> Vec<u32> {
+        let mut best = vec![0; 1 << self.flows.len()];
+        for (valve, distance) in self.from_start.iter().enumerate() {
+            if distance + 1 < minutes {
+                self.visit(valve, minutes - distance - 1, 0, 0, &mut best);
+            }
+        }
+        best
+    }
+
+    /// Opens `valve` with `minutes` left, then walks to every closed valve that can still be
+    /// opened in time.
+    fn visit(&self, valve: usize, minutes: u32, opened: usize, pressure: u32, best: &mut [u32]) {
+        let opened = opened | 1 << valve;
+        let pressure = pressure + self.flows[valve] * minutes;
+        best[opened] = best[opened].max(pressure);
+
+        let mut closed = !opened & (best.len() - 1);
+        while closed != 0 {
+            let next = closed.trailing_zeros() as usize;
+            closed &= closed - 1;
+            let distance = self.distances[valve][next];
+            if distance + 1 < minutes {
+                self.visit(next, minutes - distance - 1, opened, pressure, best);
+            }
+        }
+    }
+}
+
+/// Turns the best pressure for opening exactly a subset into the best pressure for
+/// opening any part of it.
+fn best_within_subset(mut best: Vec<u32>) -> Vec<u32> {
+    let bits = best.len().trailing_zeros();
+    for bit in 0..bits {
+        for mask in 0..best.len() {
+            if mask & 1 << bit != 0 {
+                best[mask] = best[mask].max(best[mask ^ 1 << bit]);
+            }
+        }
+    }
+    best
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    parse(input).best_per_subset(30).into_iter().max()
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    let best = parse(input).best_per_subset(26);
+    let within = best_within_subset(best.clone());
+    let all = best.len() - 1;
+    // you and the elephant open disjoint sets of valves.
+    best.iter()
+        .enumerate()
+        .map(|(mask, pressure)| pressure + within[all ^ mask])
+        .max()
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 16);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input = advent_of_code::read_file("examples", 16);
+        let network = parse(&input);
+        // BB, CC, DD, EE, HH, JJ
+        assert_eq!(network.flows, vec![13, 2, 20, 3, 22, 21]);
+        assert_eq!(network.from_start, vec![1, 2, 1, 2, 5, 2]);
+        assert_eq!(network.distances[4][5], 7);
+    }
+
+    #[test]
+    fn test_best_within_subset() {
+        assert_eq!(best_within_subset(vec![0, 5, 3, 4]), vec![0, 5, 3, 5]);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 16);
+        assert_eq!(part_one(&input), Some(1651));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 16);
+        assert_eq!(part_two(&input), Some(1707));
+    }
+}
@@ -0,0 +1,276 @@
+use advent_of_code::helpers::cycle::Detector;
+use advent_of_code::helpers::grid::Grid;
+use advent_of_code::helpers::viz::{self, Frame};
+
+const WIDTH: usize = 7;
+const LEFT_WALL: u8 = 1 << (WIDTH - 1);
+const RIGHT_WALL: u8 = 1;
+/// How many rows from the top of the tower a visualization frame shows.
+const VIEW_ROWS: usize = 30;
+
+/// The rocks in the order they fall, rows bottom up, already two columns away from the
+/// left wall. Column `x` of a row is bit `6 - x`, so pushing left is a left shift.
+const ROCKS: [&[u8]; 5] = [
+    &[0b0011110],
+    &[0b0001000, 0b0011100, 0b0001000],
+    &[0b0011100, 0b0000100, 0b0000100],
+    &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+    &[0b0011000, 0b0011000],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Jet {
+    Left,
+    Right,
+}
+
+fn parse(input: &str) -> Vec<Jet> {
+    input
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '<' => Jet::Left,
+            '>' => Jet::Right,
+            _ => panic!("invalid jet: {c}"),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Rock {
+    rows: [u8; 4],
+    height: usize,
+    /// The chamber row of the rock's bottom row.
+    y: usize,
+}
+
+impl Rock {
+    fn rows(&self) -> &[u8] {
+        &self.rows[..self.height]
+    }
+
+    fn pushed(&self, jet: Jet) -> Option<Rock> {
+        let mut rock = *self;
+        for row in &mut rock.rows[..self.height] {
+            *row = match jet {
+                Jet::Left if *row & LEFT_WALL == 0 => *row << 1,
+                Jet::Right if *row & RIGHT_WALL == 0 => *row >> 1,
+                _ => return None,
+            };
+        }
+        Some(rock)
+    }
+}
+
+struct Chamber<'a> {
+    /// Settled rock, bottom up. Only rows with rock in them are stored.
+    rows: Vec<u8>,
+    jets: &'a [Jet],
+    jet: usize,
+    rocks: usize,
+}
+
+impl Chamber<'_> {
+    fn new(jets: &[Jet]) -> Chamber<'_> {
+        Chamber {
+            rows: vec![],
+            jets,
+            jet: 0,
+            rocks: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn collides(&self, rock: &Rock) -> bool {
+        rock.rows()
+            .iter()
+            .zip(self.rows.iter().skip(rock.y))
+            .any(|(a, b)| a & b != 0)
+    }
+
+    fn spawn(&self) -> Rock {
+        let shape = ROCKS[self.rocks % ROCKS.len()];
+        let mut rows = [0; 4];
+        rows[..shape.len()].copy_from_slice(shape);
+        Rock {
+            rows,
+            height: shape.len(),
+            y: self.height() + 3,
+        }
+    }
+
+    /// Pushes and drops the next rock until it comes to rest.
+    fn drop_rock(&mut self) {
+        let mut rock = self.spawn();
+        loop {
+            let jet = self.jets[self.jet];
+            self.jet = (self.jet + 1) % self.jets.len();
+            if let Some(pushed) = rock.pushed(jet).filter(|r| !self.collides(r)) {
+                rock = pushed;
+            }
+
+            let fallen = Rock {
+                y: rock.y.wrapping_sub(1),
+                ..rock
+            };
+            if rock.y == 0 || self.collides(&fallen) {
+                break;
+            }
+            rock = fallen;
+        }
+
+        for (i, row) in rock.rows().iter().enumerate() {
+            if rock.y + i == self.rows.len() {
+                self.rows.push(0);
+            }
+            self.rows[rock.y + i] |= row;
+        }
+        self.rocks += 1;
+    }
+
+    /// How far down from the top every column is first blocked.
+    fn surface(&self) -> [usize; WIDTH] {
+        let mut surface = [0; WIDTH];
+        for (x, depth) in surface.iter_mut().enumerate() {
+            let column = LEFT_WALL >> x;
+            *depth = self
+                .rows
+                .iter()
+                .rev()
+                .position(|row| row & column != 0)
+                .unwrap_or(self.rows.len());
+        }
+        surface
+    }
+
+    /// The chamber as drawn in the puzzle description, with `falling` drawn as `@`.
+    fn render(&self, falling: Option<&Rock>) -> String {
+        let top = falling.map_or(0, |rock| rock.y + rock.height);
+        let mut out = String::new();
+        for y in (0..self.height().max(top)).rev() {
+            let settled = self.rows.get(y).copied().unwrap_or(0);
+            let moving = falling
+                .and_then(|rock| rock.rows().get(y.checked_sub(rock.y)?).copied())
+                .unwrap_or(0);
+            out.push('|');
+            for x in 0..WIDTH {
+                let column = LEFT_WALL >> x;
+                out.push(if moving & column != 0 {
+                    '@'
+                } else if settled & column != 0 {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push_str("|\n");
+        }
+        out.push_str("+-------+\n");
+        out
+    }
+}
+
+/// The tower height after `rocks` rocks. Once the next rock, the next jet and the shape of
+/// the surface repeat, so does the growth of the tower.
+fn tower_height(jets: &[Jet], rocks: usize) -> usize {
+    let mut chamber = Chamber::new(jets);
+    let mut detector = Detector::new();
+    while chamber.rocks < rocks {
+        let key = (chamber.rocks % ROCKS.len(), chamber.jet, chamber.surface());
+        if let Some(cycle) = detector.record(key, chamber.height() as i64) {
+            return detector.extrapolate(&cycle, rocks) as usize;
+        }
+        chamber.drop_rock();
+        viz::emit(|| {
+            let text = chamber.render(None);
+            let top: Vec<&str> = text.lines().take(VIEW_ROWS).collect();
+            Frame::new(Grid::parse(&top.join("\n"), |c| c)).caption(format!(
+                "{} rocks, height {}",
+                chamber.rocks,
+                chamber.height()
+            ))
+        });
+    }
+    chamber.height()
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    Some(tower_height(&parse(input), 2022))
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    Some(tower_height(&parse(input), 1_000_000_000_000))
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 17);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let jets = parse(&advent_of_code::read_file("examples", 17));
+        let mut chamber = Chamber::new(&jets);
+        assert_eq!(
+            chamber.render(Some(&chamber.spawn())),
+            "\
+|..@@@@.|
+|.......|
+|.......|
+|.......|
++-------+
+"
+        );
+
+        chamber.drop_rock();
+        chamber.drop_rock();
+        assert_eq!(
+            chamber.render(Some(&chamber.spawn())),
+            "\
+|....@..|
+|....@..|
+|..@@@..|
+|.......|
+|.......|
+|.......|
+|...#...|
+|..###..|
+|...#...|
+|..####.|
++-------+
+"
+        );
+    }
+
+    #[test]
+    fn test_cycle_skipping() {
+        let jets = parse(&advent_of_code::read_file("examples", 17));
+        let mut chamber = Chamber::new(&jets);
+        for rocks in 1..=5000 {
+            chamber.drop_rock();
+            if rocks % 1000 == 0 {
+                assert_eq!(tower_height(&jets, rocks), chamber.height());
+            }
+        }
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 17);
+        assert_eq!(part_one(&input), Some(3068));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 17);
+        assert_eq!(part_two(&input), Some(1514285714288));
+    }
+}
@@ -0,0 +1,87 @@
+use advent_of_code::helpers::point3::{bounds, Point3};
+use std::collections::{HashSet, VecDeque};
+
+fn parse(input: &str) -> HashSet<Point3> {
+    input.lines().map(Point3::from).collect()
+}
+
+/// Faces of lava cubes that do not touch another lava cube.
+fn surface_area(lava: &HashSet<Point3>) -> usize {
+    lava.iter()
+        .flat_map(|cube| cube.neighbours())
+        .filter(|n| !lava.contains(n))
+        .count()
+}
+
+/// Faces of lava cubes that steam reaching the droplet from outside can touch.
+/// Flood fills the air in a box one cube larger than the droplet on every side, so the
+/// steam can get around it, and counts the lava faces it runs into.
+fn exterior_surface_area(lava: &HashSet<Point3>) -> usize {
+    let Some((min, max)) = bounds(lava.iter().copied()) else {
+        return 0;
+    };
+    let padding = Point3::new(1, 1, 1);
+    let (min, max) = (min - padding, max + padding);
+
+    let mut steam = HashSet::from([min]);
+    let mut queue = VecDeque::from([min]);
+    let mut faces = 0;
+    while let Some(cube) = queue.pop_front() {
+        for next in cube.neighbours().filter(|n| n.within(min, max)) {
+            if lava.contains(&next) {
+                faces += 1;
+            } else if steam.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    faces
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    Some(surface_area(&parse(input)))
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    Some(exterior_surface_area(&parse(input)))
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 18);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_cubes() {
+        assert_eq!(part_one("1,1,1\n2,1,1"), Some(10));
+        assert_eq!(part_two("1,1,1\n2,1,1"), Some(10));
+    }
+
+    #[test]
+    fn test_air_pocket() {
+        // a 3x3x3 cube with its centre missing.
+        let shell: HashSet<Point3> = (0..27)
+            .map(|i| Point3::new(i % 3, i / 3 % 3, i / 9))
+            .filter(|p| *p != Point3::new(1, 1, 1))
+            .collect();
+        assert_eq!(surface_area(&shell), 54 + 6);
+        assert_eq!(exterior_surface_area(&shell), 54);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 18);
+        assert_eq!(part_one(&input), Some(64));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 18);
+        assert_eq!(part_two(&input), Some(58));
+    }
+}
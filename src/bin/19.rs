@@ -0,0 +1,193 @@
+use regex::Regex;
+use std::thread;
+
+const ORE: usize = 0;
+const CLAY: usize = 1;
+const OBSIDIAN: usize = 2;
+const GEODE: usize = 3;
+
+/// Resources other than geodes, indexed by `ORE`, `CLAY` and `OBSIDIAN`.
+type Resources = [u32; 3];
+
+#[derive(Debug, PartialEq)]
+struct Blueprint {
+    id: u32,
+    /// What a robot collecting each resource (`GEODE` included) costs.
+    costs: [Resources; 4],
+    /// The most of each resource any robot costs. Building more robots of that kind is
+    /// pointless, only one robot can be built per minute.
+    max_spend: Resources,
+}
+
+impl From<&str> for Blueprint {
+    fn from(line: &str) -> Blueprint {
+        let re = Regex::new(r"\d+").unwrap();
+        let numbers: Vec<u32> = re
+            .find_iter(line)
+            .map(|m| m.as_str().parse().unwrap())
+            .collect();
+        let [id, ore, clay, obsidian_ore, obsidian_clay, geode_ore, geode_obsidian] = numbers[..]
+        else {
+            panic!("invalid blueprint: {line}");
+        };
+        let costs = [
+            [ore, 0, 0],
+            [clay, 0, 0],
+            [obsidian_ore, obsidian_clay, 0],
+            [geode_ore, 0, geode_obsidian],
+        ];
+        let max_spend = [ORE, CLAY, OBSIDIAN].map(|r| costs.iter().map(|c| c[r]).max().unwrap());
+        Blueprint {
+            id,
+            costs,
+            max_spend,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct State {
+    minutes: u32,
+    robots: Resources,
+    resources: Resources,
+    /// Geodes opened by the end, counted as soon as their robot is built.
+    geodes: u32,
+}
+
+impl Blueprint {
+    /// Minutes until a `robot` can be built and is finished, `None` if it never can.
+    fn time_to_build(&self, state: &State, robot: usize) -> Option<u32> {
+        let mut wait = 0;
+        for r in [ORE, CLAY, OBSIDIAN] {
+            let missing = self.costs[robot][r].saturating_sub(state.resources[r]);
+            if missing > 0 {
+                if state.robots[r] == 0 {
+                    return None;
+                }
+                wait = wait.max(missing.div_ceil(state.robots[r]));
+            }
+        }
+        Some(wait + 1)
+    }
+
+    /// Skips ahead to the minute `robot` is finished, if that is before time runs out.
+    fn build(&self, state: &State, robot: usize) -> Option<State> {
+        let time = self
+            .time_to_build(state, robot)
+            .filter(|t| *t < state.minutes)?;
+        let mut next = *state;
+        next.minutes -= time;
+        for r in [ORE, CLAY, OBSIDIAN] {
+            next.resources[r] = state.resources[r] + state.robots[r] * time - self.costs[robot][r];
+        }
+        if robot == GEODE {
+            next.geodes += next.minutes;
+        } else {
+            next.robots[robot] += 1;
+        }
+        Some(next)
+    }
+
+    /// The most geodes that can be opened in `minutes`.
+    fn max_geodes(&self, minutes: u32) -> u32 {
+        let mut best = 0;
+        self.search(
+            State {
+                minutes,
+                robots: [1, 0, 0],
+                resources: [0; 3],
+                geodes: 0,
+            },
+            &mut best,
+        );
+        best
+    }
+
+    /// Depth first over which robot to build next. A branch is cut once building a geode
+    /// robot every remaining minute would not beat the best result so far.
+    fn search(&self, state: State, best: &mut u32) {
+        *best = (*best).max(state.geodes);
+        let t = state.minutes;
+        if state.geodes + t * t.saturating_sub(1) / 2 <= *best {
+            return;
+        }
+        for robot in [GEODE, OBSIDIAN, CLAY, ORE] {
+            if robot != GEODE && state.robots[robot] >= self.max_spend[robot] {
+                continue;
+            }
+            if let Some(next) = self.build(&state, robot) {
+                self.search(next, best);
+            }
+        }
+    }
+}
+
+fn parse(input: &str) -> Vec<Blueprint> {
+    input.lines().map(Blueprint::from).collect()
+}
+
+/// `max_geodes` for every blueprint, each searched on its own thread.
+fn max_geodes(blueprints: &[Blueprint], minutes: u32) -> Vec<u32> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = blueprints
+            .iter()
+            .map(|blueprint| scope.spawn(move || blueprint.max_geodes(minutes)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let blueprints = parse(input);
+    let geodes = max_geodes(&blueprints, 24);
+    Some(blueprints.iter().zip(geodes).map(|(b, g)| b.id * g).sum())
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    let blueprints = parse(input);
+    let first = &blueprints[..blueprints.len().min(3)];
+    Some(max_geodes(first, 32).into_iter().product())
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 19);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input = advent_of_code::read_file("examples", 19);
+        let blueprint = &parse(&input)[1];
+        assert_eq!(blueprint.id, 2);
+        assert_eq!(
+            blueprint.costs,
+            [[2, 0, 0], [3, 0, 0], [3, 8, 0], [3, 0, 12]]
+        );
+        assert_eq!(blueprint.max_spend, [3, 8, 12]);
+    }
+
+    #[test]
+    fn test_max_geodes() {
+        let input = advent_of_code::read_file("examples", 19);
+        let blueprints = parse(&input);
+        assert_eq!(max_geodes(&blueprints, 24), vec![9, 12]);
+        assert_eq!(max_geodes(&blueprints, 32), vec![56, 62]);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 19);
+        assert_eq!(part_one(&input), Some(33));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 19);
+        assert_eq!(part_two(&input), Some(56 * 62));
+    }
+}
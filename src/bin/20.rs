@@ -1,23 +1,34 @@
+use advent_of_code::helpers::math::Checked;
+use advent_of_code::helpers::treap::ImplicitTreap;
+
 pub fn part_one(input: &str) -> Option<i64> {
-    let result = apply_moves(input.lines().map(|l| l.parse::<i64>().unwrap()).collect(), 1, 1);
+    let result = apply_moves(
+        input.lines().map(|l| l.parse::<i64>().unwrap()).collect(),
+        1,
+        1,
+    );
     let len = result.len();
     let zero = result.iter().position(|&i| i == 0).unwrap();
     let sum = [1000, 2000, 3000]
         .into_iter()
-        .map(|i| result[(i + zero) % len])
-        .sum();
-    Some(sum)
+        .map(|i| Checked(result[(i + zero) % len]))
+        .sum::<Checked<i64>>();
+    Some(sum.0)
 }
 
 pub fn part_two(input: &str) -> Option<i64> {
-    let result = apply_moves(input.lines().map(|l| l.parse::<i64>().unwrap()).collect(), 811589153, 10);
+    let result = apply_moves(
+        input.lines().map(|l| l.parse::<i64>().unwrap()).collect(),
+        811589153,
+        10,
+    );
     let len = result.len();
     let zero = result.iter().position(|&i| i == 0).unwrap();
     let sum = [1000, 2000, 3000]
         .into_iter()
-        .map(|i| result[(i + zero) % len])
-        .sum();
-    Some(sum)
+        .map(|i| Checked(result[(i + zero) % len]))
+        .sum::<Checked<i64>>();
+    Some(sum.0)
 }
 
 fn main() {
@@ -26,30 +37,71 @@ fn main() {
     advent_of_code::solve!(2, part_two, input);
 }
 
+/// Mixes the numbers `times` times. Every number keeps a handle into the treap, so finding
+/// its current position and moving it are both O(log n).
 fn apply_moves(initial: Vec<i64>, multiplicator: i64, times: usize) -> Vec<i64> {
     let len = initial.len() as i64;
-    let mut tuples: Vec<_> = initial.into_iter().map(|n| n * multiplicator).enumerate().collect();
-
-    for j in 0..times {
-        for i in 0..(len as usize) {
-            let src_idx = tuples.iter().position(|tuple| tuple.0 == i).unwrap();
-    
-            let tuple = tuples.remove(src_idx);
-            let dst_idx = (src_idx as i64) + tuple.1;
-            let dst_idx = dst_idx.rem_euclid(len - 1) as usize;
-            tuples.insert(dst_idx, tuple);
+    let mut list = ImplicitTreap::new();
+    let handles: Vec<_> = initial
+        .into_iter()
+        .map(|n| list.push((Checked(n) * Checked(multiplicator)).0))
+        .collect();
+
+    for _ in 0..times {
+        for &handle in &handles {
+            let src_idx = list.rank(handle) as i64;
+            let dst_idx = (src_idx + list.get(handle)).rem_euclid(len - 1) as usize;
+            list.move_to(handle, dst_idx);
         }
     }
 
-    tuples.into_iter().map(|(_, val)| val).collect()
+    list.iter().copied().collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Instant;
 
-    #[test]
+    /// The previous O(n²) mixing, kept as a reference for `bench_moves`.
+    fn apply_moves_naive(initial: Vec<i64>, multiplicator: i64, times: usize) -> Vec<i64> {
+        let len = initial.len() as i64;
+        let mut tuples: Vec<_> = initial
+            .into_iter()
+            .map(|n| n * multiplicator)
+            .enumerate()
+            .collect();
+
+        for _ in 0..times {
+            for i in 0..(len as usize) {
+                let src_idx = tuples.iter().position(|tuple| tuple.0 == i).unwrap();
+                let tuple = tuples.remove(src_idx);
+                let dst_idx = ((src_idx as i64) + tuple.1).rem_euclid(len - 1) as usize;
+                tuples.insert(dst_idx, tuple);
+            }
+        }
+
+        tuples.into_iter().map(|(_, val)| val).collect()
+    }
+
+    /// 5000 numbers in the range of the real input, with a single zero.
+    fn large_input() -> Vec<i64> {
+        let mut seed = 0x9e37_79b9_u64;
+        (0..5000)
+            .map(|i| {
+                if i == 2500 {
+                    return 0;
+                }
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                match ((seed >> 33) % 20000) as i64 - 10000 {
+                    0 => 1,
+                    n => n,
+                }
+            })
+            .collect()
+    }
 
+    #[test]
     fn test_moves() {
         let initial = vec![1, 2, -3, 3, -2, 0, 4];
         assert_eq!(apply_moves(initial, 1, 1), vec![-2, 1, 2, -3, 4, 0, 3]);
@@ -66,4 +118,32 @@ mod tests {
         let input = advent_of_code::read_file("examples", 20);
         assert_eq!(part_two(&input), Some(1623178306));
     }
+
+    #[test]
+    fn test_moves_large() {
+        let input = large_input();
+        assert_eq!(
+            apply_moves(input.clone(), 811589153, 2),
+            apply_moves_naive(input, 811589153, 2)
+        );
+    }
+
+    /// Run with `cargo test --release --bin 20 -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_moves() {
+        let input = large_input();
+
+        let start = Instant::now();
+        let naive = apply_moves_naive(input.clone(), 811589153, 10);
+        let naive_time = start.elapsed();
+
+        let start = Instant::now();
+        let treap = apply_moves(input, 811589153, 10);
+        let treap_time = start.elapsed();
+
+        assert_eq!(naive, treap);
+        println!("naive: {:.2?}, treap: {:.2?}", naive_time, treap_time);
+        assert!(treap_time < naive_time);
+    }
 }
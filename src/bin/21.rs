@@ -1,8 +1,13 @@
-use std::{cmp::Ordering, collections::HashMap, thread::panicking};
+use advent_of_code::helpers::math::Rational;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
 
 type Expressions<'a> = HashMap<&'a str, Expression<'a>>;
 
-fn parse(input: &str) -> Expressions {
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+fn parse(input: &str) -> Expressions<'_> {
     input
         .lines()
         .map(|line| {
@@ -21,15 +26,6 @@ enum Expression<'a> {
     Const(i64),
 }
 
-impl Expression<'_> {
-    fn solve(&self, expressions: &Expressions) -> i64 {
-        match self {
-            Self::Dynamic(op) => op.call(expressions),
-            Self::Const(i) => *i,
-        }
-    }
-}
-
 impl<'a> From<&'a str> for Expression<'a> {
     fn from(value: &'a str) -> Expression<'a> {
         if let Ok(value) = value.parse::<i64>() {
@@ -40,95 +36,368 @@ impl<'a> From<&'a str> for Expression<'a> {
     }
 }
 
-#[derive(Debug)]
-struct Operation<'a> {
-    a: &'a str,
-    op: &'a str,
-    b: &'a str,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
 }
 
-impl Operation<'_> {
-    fn call(&self, expressions: &Expressions) -> i64 {
-        let a = expressions.get(self.a).unwrap();
-        let b = expressions.get(self.b).unwrap();
-        let op = match self.op {
-            "+" => std::ops::Add::add,
-            "-" => std::ops::Sub::sub,
-            "*" => std::ops::Mul::mul,
-            "/" => std::ops::Div::div,
+impl Operator {
+    /// Applies the operator for the monkey `name`. Monkeys only yell integers, so a
+    /// division that does not come out even is an error.
+    fn apply<'a>(
+        &self,
+        name: &'a str,
+        a: Rational,
+        b: Rational,
+    ) -> Result<Rational, EvalError<'a>> {
+        let value = match self {
+            Operator::Add => a + b,
+            Operator::Sub => a - b,
+            Operator::Mul => a * b,
+            Operator::Div if b == Rational::from(0) => return Err(EvalError::DivisionByZero(name)),
+            Operator::Div => a / b,
+        };
+        if value.denominator() != 1 {
+            return Err(EvalError::InexactDivision(name));
+        }
+        Ok(value)
+    }
+
+    /// Solves `unknown <op> known = result` for `unknown`, `None` if any value would do or
+    /// none does.
+    fn solve_left(&self, result: Rational, known: Rational) -> Option<Rational> {
+        let zero = Rational::from(0);
+        match self {
+            Operator::Add => Some(result - known),
+            Operator::Sub => Some(result + known),
+            Operator::Mul if known == zero => None,
+            Operator::Mul => Some(result / known),
+            Operator::Div if known == zero => None,
+            Operator::Div => Some(result * known),
+        }
+    }
+
+    /// Solves `known <op> unknown = result` for `unknown`, `None` if any value would do or
+    /// none does.
+    fn solve_right(&self, result: Rational, known: Rational) -> Option<Rational> {
+        let zero = Rational::from(0);
+        match self {
+            Operator::Add => Some(result - known),
+            Operator::Sub => Some(known - result),
+            Operator::Mul if known == zero => None,
+            Operator::Mul => Some(result / known),
+            Operator::Div if result == zero => None,
+            Operator::Div => Some(known / result),
+        }
+    }
+}
+
+impl From<&str> for Operator {
+    fn from(value: &str) -> Operator {
+        match value {
+            "+" => Operator::Add,
+            "-" => Operator::Sub,
+            "*" => Operator::Mul,
+            "/" => Operator::Div,
             _ => panic!(),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
         };
-        op(a.solve(expressions), b.solve(expressions))
+        write!(f, "{}", symbol)
     }
 }
 
+#[derive(Debug)]
+struct Operation<'a> {
+    a: &'a str,
+    op: Operator,
+    b: &'a str,
+}
+
 impl<'a> From<&'a str> for Operation<'a> {
     fn from(input: &'a str) -> Operation<'a> {
         if let [a, op, b] = &input.split_whitespace().collect::<Vec<&str>>()[..] {
-            Operation { a, b, op }
+            Operation {
+                a,
+                op: Operator::from(*op),
+                b,
+            }
         } else {
             panic!()
         }
     }
 }
 
-pub fn part_one(input: &str) -> Option<i64> {
-    let mut expressions = parse(input);
-    let root = expressions.remove("root").unwrap();
+#[derive(Debug, PartialEq)]
+enum EvalError<'a> {
+    /// A monkey refers to a name nobody yells.
+    Undefined(&'a str),
+    /// The names, in order, of monkeys that wait on each other in a loop.
+    Cycle(Vec<&'a str>),
+    /// A monkey divides by zero.
+    DivisionByZero(&'a str),
+    /// A monkey divides two numbers that do not divide evenly.
+    InexactDivision(&'a str),
+}
 
-    Some(root.solve(&expressions))
+impl EvalError<'_> {
+    /// Whether the input itself is broken, rather than just not adding up to integers.
+    fn is_malformed(&self) -> bool {
+        matches!(self, EvalError::Undefined(_) | EvalError::Cycle(_))
+    }
 }
 
-pub fn part_two(input: &str) -> Option<i64> {
-    let mut expressions = parse(input);
-    let root = expressions.remove("root").unwrap();
-    let left;
-    let right;
-    if let Expression::Dynamic(op) = root {
-        left = expressions.remove(op.a).unwrap();
-        right = expressions.remove(op.b).unwrap();
-    } else {
-        panic!()
-    };
+impl Display for EvalError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Undefined(name) => write!(f, "no monkey named `{}`", name),
+            EvalError::Cycle(names) => write!(f, "cyclic reference: {}", names.join(" -> ")),
+            EvalError::DivisionByZero(name) => write!(f, "`{}` divides by zero", name),
+            EvalError::InexactDivision(name) => {
+                write!(f, "`{}` divides numbers that do not divide evenly", name)
+            }
+        }
+    }
+}
 
-    let mut i = 0;
-    let mut test = 0;
-    let mut incr = 100000000;
-    let right_res = right.solve(&expressions);
-    let init_comp = right_res.cmp(&left.solve(&expressions));
-    loop {
-        expressions.insert("humn", Expression::Const(test));
-        let left_res = left.solve(&expressions);
-        let comp = right_res.cmp(&left_res);
-        match comp {
-            Ordering::Equal => return Some(test),
-            comp => {
-                if i % 1000 == 0 {
-                    println!("{} != {} ({} {})", left_res, right_res, test, incr);
+/// A constant-folded expression tree: every subtree that does not depend on the
+/// unknown is replaced by its value.
+#[derive(Debug, PartialEq)]
+enum Node<'a> {
+    Const(Rational),
+    Unknown(&'a str),
+    Op(Box<Node<'a>>, Operator, Box<Node<'a>>),
+}
+
+impl Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Const(value) if value.denominator() == 1 => write!(f, "{}", value),
+            Node::Const(value) => write!(f, "({})", value),
+            Node::Unknown(name) => write!(f, "{}", name),
+            Node::Op(a, op, b) => write!(f, "({} {} {})", a, op, b),
+        }
+    }
+}
+
+impl Node<'_> {
+    /// Writes this node and its children as Graphviz statements, returns the node's id.
+    fn write_dot(&self, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        match self {
+            Node::Const(value) => out.push_str(&format!("  n{} [label=\"{}\"];\n", id, value)),
+            Node::Unknown(name) => {
+                out.push_str(&format!("  n{} [label=\"{}\", shape=box];\n", id, name))
+            }
+            Node::Op(a, op, b) => {
+                out.push_str(&format!("  n{} [label=\"{}\", shape=circle];\n", id, op));
+                for child in [a, b] {
+                    let child_id = child.write_dot(next_id, out);
+                    out.push_str(&format!("  n{} -> n{};\n", id, child_id));
                 }
-                if comp != init_comp {
-                    println!(
-                        "DECR {} {:?} {} (test={} incr={} i={})",
-                        left_res, comp, right_res, test, incr, i
-                    );
-                    test -= incr;
-                    incr /= 10;
-                    if incr == 0 {
-                        panic!()
-                    }
-                } else {
-                    test += incr;
+            }
+        }
+        id
+    }
+}
+
+/// The equation `left = right` as a Graphviz `digraph`.
+fn equation_to_dot(left: &Node, right: &Node) -> String {
+    let mut out = String::from("digraph {\n  n0 [label=\"=\", shape=circle];\n");
+    let mut next_id = 1;
+    for side in [left, right] {
+        let id = side.write_dot(&mut next_id, &mut out);
+        out.push_str(&format!("  n0 -> n{};\n", id));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Evaluates expressions by name, remembering every value it computed.
+struct Evaluator<'e, 'a> {
+    expressions: &'e Expressions<'a>,
+    cache: HashMap<&'a str, Rational>,
+    stack: Vec<&'a str>,
+}
+
+impl<'e, 'a> Evaluator<'e, 'a> {
+    fn new(expressions: &'e Expressions<'a>) -> Self {
+        Evaluator {
+            expressions,
+            cache: HashMap::new(),
+            stack: vec![],
+        }
+    }
+
+    fn lookup(&mut self, name: &'a str) -> Result<&'e Expression<'a>, EvalError<'a>> {
+        if let Some(i) = self.stack.iter().position(|n| *n == name) {
+            let mut cycle = self.stack[i..].to_vec();
+            cycle.push(name);
+            return Err(EvalError::Cycle(cycle));
+        }
+        self.expressions.get(name).ok_or(EvalError::Undefined(name))
+    }
+
+    fn value(&mut self, name: &'a str) -> Result<Rational, EvalError<'a>> {
+        if let Some(value) = self.cache.get(name) {
+            return Ok(*value);
+        }
+        let value = match self.lookup(name)? {
+            Expression::Const(i) => Rational::from(*i),
+            Expression::Dynamic(op) => {
+                self.stack.push(name);
+                let a = self.value(op.a)?;
+                let b = self.value(op.b)?;
+                self.stack.pop();
+                op.op.apply(name, a, b)?
+            }
+        };
+        self.cache.insert(name, value);
+        Ok(value)
+    }
+
+    /// The tree below `name` with `unknown` left as a variable and everything else folded.
+    fn fold(&mut self, name: &'a str, unknown: &str) -> Result<Node<'a>, EvalError<'a>> {
+        if name == unknown {
+            return Ok(Node::Unknown(name));
+        }
+        if let Some(value) = self.cache.get(name) {
+            return Ok(Node::Const(*value));
+        }
+        let node = match self.lookup(name)? {
+            Expression::Const(i) => Node::Const(Rational::from(*i)),
+            Expression::Dynamic(op) => {
+                self.stack.push(name);
+                let a = self.fold(op.a, unknown)?;
+                let b = self.fold(op.b, unknown)?;
+                self.stack.pop();
+                match (a, b) {
+                    (Node::Const(a), Node::Const(b)) => Node::Const(op.op.apply(name, a, b)?),
+                    (a, b) => Node::Op(Box::new(a), op.op, Box::new(b)),
                 }
-                i += 1;
             }
+        };
+        if let Node::Const(value) = node {
+            self.cache.insert(name, value);
+        }
+        Ok(node)
+    }
+}
+
+/// Solves `node = target` for the unknown, if it occurs exactly once. Every subtree has
+/// to take an integer value for all divisions on the way to come out even.
+fn solve_for_unknown(mut node: &Node, mut target: Rational) -> Option<Rational> {
+    loop {
+        if target.denominator() != 1 {
+            return None;
+        }
+        match node {
+            Node::Unknown(_) => return Some(target),
+            Node::Const(_) => return None,
+            Node::Op(a, op, b) => match (a.as_ref(), b.as_ref()) {
+                (unknown, Node::Const(known)) => {
+                    target = op.solve_left(target, *known)?;
+                    node = unknown;
+                }
+                (Node::Const(known), unknown) => {
+                    target = op.solve_right(target, *known)?;
+                    node = unknown;
+                }
+                _ => return None,
+            },
+        }
+    }
+}
+
+/// Panics on malformed input, arithmetic without an integer answer gives `None`.
+fn answer<T>(result: Result<T, EvalError>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) if err.is_malformed() => panic!("invalid input: {}", err),
+        Err(_) => None,
+    }
+}
+
+/// Both sides of `root`'s equality check, folded with `humn` as the unknown.
+fn equation<'a>(expressions: &Expressions<'a>) -> Result<(Node<'a>, Node<'a>), EvalError<'a>> {
+    let mut evaluator = Evaluator::new(expressions);
+    match evaluator.lookup(ROOT)? {
+        Expression::Dynamic(op) => {
+            evaluator.stack.push(ROOT);
+            Ok((evaluator.fold(op.a, HUMAN)?, evaluator.fold(op.b, HUMAN)?))
         }
+        Expression::Const(_) => panic!("`root` has to compare two monkeys"),
     }
 }
 
+pub fn part_one(input: &str) -> Option<i64> {
+    let expressions = parse(input);
+    answer(Evaluator::new(&expressions).value(ROOT))?.to_integer()
+}
+
+pub fn part_two(input: &str) -> Option<i64> {
+    let expressions = parse(input);
+    let (left, right) = answer(equation(&expressions))?;
+
+    let result = match (left, right) {
+        (Node::Const(target), unknown) | (unknown, Node::Const(target)) => {
+            solve_for_unknown(&unknown, target)
+        }
+        _ => None,
+    };
+    result?.to_integer()
+}
+
 fn main() {
     let input = &advent_of_code::read_file("inputs", 21);
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
+
+    // `cargo solve 21 -- --show infix` prints the simplified part two equation, `--export
+    // <file>` writes it as a Graphviz digraph.
+    let mut args = pico_args::Arguments::from_env();
+    let show = args
+        .opt_value_from_str::<_, String>("--show")
+        .ok()
+        .flatten();
+    let export = args
+        .opt_value_from_str::<_, String>("--export")
+        .ok()
+        .flatten();
+    if show.is_none() && export.is_none() {
+        return;
+    }
+    let expressions = parse(input);
+    let (left, right) = match equation(&expressions) {
+        Ok(equation) => equation,
+        Err(err) => {
+            eprintln!("invalid input: {}", err);
+            return;
+        }
+    };
+    match show.as_deref() {
+        Some("infix") => println!("{} = {}", left, right),
+        Some(format) => eprintln!("unknown format {format}, expected infix"),
+        None => {}
+    }
+    if let Some(file) = export {
+        if let Err(err) = std::fs::write(&file, equation_to_dot(&left, &right)) {
+            eprintln!("could not write {file}: {err}");
+        }
+    }
 }
 
 #[cfg(test)]
@@ -146,4 +415,94 @@ mod tests {
         let input = advent_of_code::read_file("examples", 21);
         assert_eq!(part_two(&input), Some(301));
     }
+
+    #[test]
+    fn test_inexact_division() {
+        // 7 / 2 is not an integer, even though multiplying by two again would make it one.
+        let input = "root: half * twoo\nhalf: seven / twoo\nseven: 7\ntwoo: 2";
+        let expressions = parse(input);
+        assert_eq!(
+            Evaluator::new(&expressions).value(ROOT),
+            Err(EvalError::InexactDivision("half"))
+        );
+        assert_eq!(part_one(input), None);
+
+        let expressions = parse("root: seven / zero\nseven: 7\nzero: 0");
+        assert_eq!(
+            Evaluator::new(&expressions).value(ROOT),
+            Err(EvalError::DivisionByZero("root"))
+        );
+    }
+
+    #[test]
+    fn test_no_integer_solution() {
+        // humn * 2 == 5
+        let input = "root: prod + five\nprod: humn * twoo\ntwoo: 2\nfive: 5\nhumn: 0";
+        assert_eq!(part_two(input), None);
+        // humn * 0 == 5
+        let input = "root: prod + five\nprod: humn * zero\nzero: 0\nfive: 5\nhumn: 0";
+        assert_eq!(part_two(input), None);
+        // 10 / humn == 0
+        let input = "root: quot + zero\nquot: tenn / humn\ntenn: 10\nzero: 0\nhumn: 1";
+        assert_eq!(part_two(input), None);
+        // humn / 0 == 5
+        let input = "root: quot + five\nquot: humn / zero\nzero: 0\nfive: 5\nhumn: 0";
+        assert_eq!(part_two(input), None);
+    }
+
+    #[test]
+    fn test_unknown_on_the_right() {
+        // 30 / (4 - humn) == 5
+        let input = "root: lhs + rhs\nlhs: thrt / diff\nthrt: 30\ndiff: four - humn\nfour: 4\nrhs: 5\nhumn: 0";
+        assert_eq!(part_two(input), Some(-2));
+    }
+
+    #[test]
+    fn test_errors() {
+        let expressions = parse("root: aaaa + bbbb\naaaa: 1\nbbbb: cccc * aaaa\ncccc: bbbb - aaaa");
+        assert_eq!(
+            Evaluator::new(&expressions).value(ROOT),
+            Err(EvalError::Cycle(vec!["bbbb", "cccc", "bbbb"]))
+        );
+
+        let expressions = parse("root: aaaa + bbbb\naaaa: 1");
+        let err = Evaluator::new(&expressions).value(ROOT).unwrap_err();
+        assert_eq!(err, EvalError::Undefined("bbbb"));
+        assert_eq!(err.to_string(), "no monkey named `bbbb`");
+    }
+
+    #[test]
+    fn test_export() {
+        let input = advent_of_code::read_file("examples", 21);
+        let expressions = parse(&input);
+        let (left, right) = equation(&expressions).unwrap();
+        assert_eq!(
+            format!("{} = {}", left, right),
+            "((4 + (2 * (humn - 3))) / 4) = 150"
+        );
+
+        let dot = equation_to_dot(
+            &Node::Op(
+                Box::new(Node::Unknown(HUMAN)),
+                Operator::Div,
+                Box::new(Node::Const(Rational::from(2))),
+            ),
+            &Node::Const(Rational::new(1, 3)),
+        );
+        assert_eq!(
+            dot,
+            "digraph {
+  n0 [label=\"=\", shape=circle];
+  n1 [label=\"/\", shape=circle];
+  n2 [label=\"humn\", shape=box];
+  n1 -> n2;
+  n3 [label=\"2\"];
+  n1 -> n3;
+  n0 -> n1;
+  n4 [label=\"1/3\"];
+  n0 -> n4;
+}
+"
+        );
+    }
 }
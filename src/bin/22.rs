@@ -1,3 +1,5 @@
+use advent_of_code::helpers::grid::Grid;
+use advent_of_code::helpers::viz::{self, Frame};
 use array2d::Array2D;
 use regex::Regex;
 
@@ -40,6 +42,17 @@ impl Direction {
     }
 }
 
+impl Direction {
+    fn arrow(&self) -> char {
+        match self {
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Up => '^',
+        }
+    }
+}
+
 impl From<&str> for Direction {
     fn from(c: &str) -> Direction {
         match c {
@@ -91,29 +104,21 @@ fn parse_actions(actions_input: &str) -> Vec<Action> {
     actions
 }
 
-fn walk(
-    position: &(usize, usize),
-    direction: &Direction,
-    num_rows: usize,
-    num_columns: usize,
-) -> (usize, usize) {
-    match direction {
-        Direction::Right => ((position.0 + 1).rem_euclid(num_columns), position.1),
-        Direction::Down => (position.0, (position.1 + 1).rem_euclid(num_rows)),
-        Direction::Left => (
-            (position.0 as i32 - 1).rem_euclid(num_columns as i32) as usize,
-            position.1,
-        ),
-        Direction::Up => (
-            position.0,
-            (position.1 as i32 - 1).rem_euclid(num_rows as i32) as usize,
-        ),
-    }
+fn map_frame(map: &Array2D<Block>, position: &(usize, usize), direction: &Direction) -> Frame {
+    let cells = map
+        .elements_row_major_iter()
+        .map(|block| match block {
+            Block::Void => ' ',
+            Block::Open => '.',
+            Block::Wall => '#',
+        })
+        .collect();
+    let mut grid = Grid::new(map.num_columns(), map.num_rows(), cells);
+    grid[*position] = direction.arrow();
+    Frame::new(grid).highlight((position.0 as i64, position.1 as i64))
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut input_parts = input.split("\n\n");
-    let map_input = input_parts.next().unwrap();
+fn parse_map(map_input: &str) -> Array2D<Block> {
     let map_raw = map_input
         .lines()
         .map(|l| l.chars().map(Block::from).collect::<Vec<Block>>())
@@ -125,7 +130,7 @@ pub fn part_one(input: &str) -> Option<u32> {
         .expect("max row length");
     let num_rows = map_raw.len();
 
-    let map = Array2D::from_iter_row_major(
+    Array2D::from_iter_row_major(
         map_raw.into_iter().flat_map(|row| {
             row.into_iter()
                 .chain(std::iter::repeat(Block::Void))
@@ -134,11 +139,200 @@ pub fn part_one(input: &str) -> Option<u32> {
         num_rows,
         num_columns,
     )
-    .expect("to array2d");
+    .expect("to array2d")
+}
+
+fn parse(input: &str) -> (Array2D<Block>, Vec<Action>) {
+    let mut input_parts = input.split("\n\n");
+    let map = parse_map(input_parts.next().unwrap());
+    let actions = parse_actions(input_parts.next().unwrap());
+    (map, actions)
+}
+
+fn password(position: &(usize, usize), direction: &Direction) -> u32 {
+    1000 * (position.1 + 1) as u32 + 4 * (position.0 + 1) as u32 + direction.value() as u32
+}
+
+fn walk(
+    position: &(usize, usize),
+    direction: &Direction,
+    num_rows: usize,
+    num_columns: usize,
+) -> (usize, usize) {
+    match direction {
+        Direction::Right => ((position.0 + 1).rem_euclid(num_columns), position.1),
+        Direction::Down => (position.0, (position.1 + 1).rem_euclid(num_rows)),
+        Direction::Left => (
+            (position.0 as i32 - 1).rem_euclid(num_columns as i32) as usize,
+            position.1,
+        ),
+        Direction::Up => (
+            position.0,
+            (position.1 as i32 - 1).rem_euclid(num_rows as i32) as usize,
+        ),
+    }
+}
+
+type Vec3 = [i32; 3];
+
+fn neg(v: Vec3) -> Vec3 {
+    [-v[0], -v[1], -v[2]]
+}
+
+fn dot(a: Vec3, b: Vec3) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// A face of the folded cube: where it sits in the net and how it is oriented in space.
+/// `right` and `down` are the directions the net's x and y axes point to on this face.
+#[derive(Debug)]
+struct Face {
+    origin: (usize, usize),
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Face {
+    fn axis(&self, direction: &Direction) -> Vec3 {
+        match direction {
+            Direction::Right => self.right,
+            Direction::Down => self.down,
+            Direction::Left => neg(self.right),
+            Direction::Up => neg(self.down),
+        }
+    }
+
+    /// Rolls the cube over the edge in `direction`, yielding the orientation of the face behind it.
+    fn fold(&self, direction: &Direction, origin: (usize, usize)) -> Face {
+        let (normal, right, down) = (self.normal, self.right, self.down);
+        let (normal, right, down) = match direction {
+            Direction::Right => (right, neg(normal), down),
+            Direction::Left => (neg(right), normal, down),
+            Direction::Down => (down, right, neg(normal)),
+            Direction::Up => (neg(down), right, normal),
+        };
+        Face {
+            origin,
+            normal,
+            right,
+            down,
+        }
+    }
+}
+
+/// The map folded into a cube. Works for any of the 11 cube nets.
+#[derive(Debug)]
+struct Cube {
+    size: usize,
+    faces: Vec<Face>,
+}
+
+impl Cube {
+    fn fold(map: &Array2D<Block>) -> Cube {
+        let area = map
+            .elements_row_major_iter()
+            .filter(|b| **b != Block::Void)
+            .count();
+        let size = ((area / 6) as f64).sqrt() as usize;
+        assert_eq!(size * size * 6, area, "map is not a cube net");
+
+        let is_face = |(x, y): (usize, usize)| {
+            matches!(map.get(y * size, x * size), Some(Block::Open | Block::Wall))
+        };
+        let first = (0..map.num_columns() / size)
+            .find(|x| is_face((*x, 0)))
+            .unwrap();
+
+        let mut faces = vec![Face {
+            origin: (first, 0),
+            normal: [0, 0, 1],
+            right: [1, 0, 0],
+            down: [0, 1, 0],
+        }];
+        let mut i = 0;
+        while i < faces.len() {
+            let (x, y) = faces[i].origin;
+            let neighbours = [
+                (Direction::Right, Some((x + 1, y))),
+                (Direction::Down, Some((x, y + 1))),
+                (Direction::Left, x.checked_sub(1).map(|x| (x, y))),
+                (Direction::Up, y.checked_sub(1).map(|y| (x, y))),
+            ];
+            for (direction, neighbour) in neighbours {
+                if let Some(origin) = neighbour {
+                    if is_face(origin) && faces.iter().all(|f| f.origin != origin) {
+                        let face = faces[i].fold(&direction, origin);
+                        faces.push(face);
+                    }
+                }
+            }
+            i += 1;
+        }
+        assert_eq!(faces.len(), 6, "map is not a cube net");
+
+        Cube { size, faces }
+    }
+
+    fn face(&self, position: &(usize, usize)) -> &Face {
+        let origin = (position.0 / self.size, position.1 / self.size);
+        self.faces.iter().find(|f| f.origin == origin).unwrap()
+    }
+
+    /// The position one step ahead and the direction to continue in, crossing edges as needed.
+    fn step(
+        &self,
+        position: &(usize, usize),
+        direction: &Direction,
+    ) -> ((usize, usize), Direction) {
+        let size = self.size;
+        let (x, y) = (position.0 % size, position.1 % size);
+        let inside = match direction {
+            Direction::Right => (x + 1 < size).then(|| (x + 1, y)),
+            Direction::Down => (y + 1 < size).then(|| (x, y + 1)),
+            Direction::Left => x.checked_sub(1).map(|x| (x, y)),
+            Direction::Up => y.checked_sub(1).map(|y| (x, y)),
+        };
+        let face = self.face(position);
+        if let Some((x, y)) = inside {
+            let (fx, fy) = face.origin;
+            return ((fx * size + x, fy * size + y), *direction);
+        }
+
+        // cell centres in doubled coordinates, the cube spans -size..=size on every axis.
+        let n = size as i32;
+        let centre: Vec3 = [0, 1, 2].map(|i| {
+            face.normal[i] * n
+                + face.right[i] * (2 * x as i32 + 1 - n)
+                + face.down[i] * (2 * y as i32 + 1 - n)
+        });
+        let axis = face.axis(direction);
+        let next = self.faces.iter().find(|f| f.normal == axis).unwrap();
+        let next_centre: Vec3 = [0, 1, 2].map(|i| centre[i] + axis[i] - face.normal[i]);
+
+        let x = ((dot(next_centre, next.right) + n - 1) / 2) as usize;
+        let y = ((dot(next_centre, next.down) + n - 1) / 2) as usize;
+        let heading = neg(face.normal);
+        let next_direction = [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ]
+        .into_iter()
+        .find(|d| next.axis(d) == heading)
+        .unwrap();
+        let (fx, fy) = next.origin;
+        ((fx * size + x, fy * size + y), next_direction)
+    }
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let (map, actions) = parse(input);
+    let (num_rows, num_columns) = (map.num_rows(), map.num_columns());
 
     let mut direction = Direction::Right;
     let mut position = find_start(&map);
-    let actions = parse_actions(input_parts.next().unwrap());
 
     for action in actions {
         match action {
@@ -170,13 +364,42 @@ pub fn part_one(input: &str) -> Option<u32> {
                 direction = direction.rotate(&rot);
             }
         }
+        viz::emit(|| map_frame(&map, &position, &direction));
     }
 
-    Some(1000 * (position.1 + 1) as u32 + 4 * (position.0 + 1) as u32 + direction.value() as u32)
+    Some(password(&position, &direction))
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    None
+    let (map, actions) = parse(input);
+    let cube = Cube::fold(&map);
+
+    let mut direction = Direction::Right;
+    let mut position = find_start(&map);
+
+    for action in actions {
+        match action {
+            Action::Walk(dist) => {
+                for _ in 0..dist {
+                    let (next_position, next_direction) = cube.step(&position, &direction);
+                    match map.get(next_position.1, next_position.0) {
+                        Some(Block::Open) => {
+                            position = next_position;
+                            direction = next_direction;
+                        }
+                        Some(Block::Wall) => break,
+                        _ => panic!("stepped off the cube"),
+                    }
+                }
+            }
+            Action::Rotate(rot) => {
+                direction = direction.rotate(&rot);
+            }
+        }
+        viz::emit(|| map_frame(&map, &position, &direction));
+    }
+
+    Some(password(&position, &direction))
 }
 
 fn main() {
@@ -198,6 +421,66 @@ mod tests {
     #[test]
     fn test_part_two() {
         let input = advent_of_code::read_file("examples", 22);
-        assert_eq!(part_two(&input), None);
+        assert_eq!(part_two(&input), Some(5031));
+    }
+
+    const NETS: [&str; 11] = [
+        "#...\n####\n#...",
+        "#...\n####\n.#..",
+        "#...\n####\n..#.",
+        "#...\n####\n...#",
+        ".#..\n####\n.#..",
+        ".#..\n####\n..#.",
+        "##..\n.###\n.#..",
+        "##..\n.###\n..#.",
+        "##..\n.###\n...#",
+        "##..\n.##.\n..##",
+        "###...\n..###.",
+    ];
+
+    fn open_map(net: &str, size: usize) -> Array2D<Block> {
+        let map_input = net
+            .lines()
+            .flat_map(|line| {
+                let row: String = line
+                    .chars()
+                    .flat_map(|c| std::iter::repeat_n(if c == '#' { '.' } else { ' ' }, size))
+                    .collect();
+                std::iter::repeat_n(row, size)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        parse_map(&map_input)
+    }
+
+    #[test]
+    fn test_fold_nets() {
+        for net in NETS {
+            let size = 3;
+            let map = open_map(net, size);
+            let cube = Cube::fold(&map);
+
+            // going straight ahead on a cube leads back to the start after four faces.
+            for (row, column) in map.indices_row_major() {
+                if map[(row, column)] == Block::Void {
+                    continue;
+                }
+                for direction in [
+                    Direction::Right,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Up,
+                ] {
+                    let start = (column, row);
+                    let (mut position, mut heading) = (start, direction);
+                    for _ in 0..4 * size {
+                        (position, heading) = cube.step(&position, &heading);
+                        assert_ne!(map[(position.1, position.0)], Block::Void, "{}", net);
+                    }
+                    assert_eq!(position, start, "{}", net);
+                    assert_eq!(heading.value(), direction.value(), "{}", net);
+                }
+            }
+        }
     }
 }
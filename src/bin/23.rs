@@ -0,0 +1,199 @@
+use advent_of_code::helpers::grid::Grid;
+use advent_of_code::helpers::viz::{self, Frame};
+use std::collections::{HashMap, HashSet};
+
+type Pos = (i64, i64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    fn step(self) -> Pos {
+        match self {
+            Direction::North => (0, -1),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+            Direction::East => (1, 0),
+        }
+    }
+
+    /// The three neighbours on this side that have to be empty to move this way.
+    fn checks(self, (x, y): Pos) -> [Pos; 3] {
+        let (dx, dy) = self.step();
+        if dx == 0 {
+            [-1, 0, 1].map(|d| (x + d, y + dy))
+        } else {
+            [-1, 0, 1].map(|d| (x + dx, y + d))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Grove {
+    elves: HashSet<Pos>,
+    /// The order directions are considered in, the first moves to the back every round.
+    order: [Direction; 4],
+    rounds: usize,
+}
+
+impl From<&str> for Grove {
+    fn from(input: &str) -> Grove {
+        let elves = input
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x as i64, y as i64))
+            })
+            .collect();
+        Grove {
+            elves,
+            order: [
+                Direction::North,
+                Direction::South,
+                Direction::West,
+                Direction::East,
+            ],
+            rounds: 0,
+        }
+    }
+}
+
+impl Grove {
+    /// Where the elf at `pos` wants to go, `None` if it stays put.
+    fn proposal(&self, (x, y): Pos) -> Option<Pos> {
+        let crowded = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .any(|n| n != (x, y) && self.elves.contains(&n));
+        if !crowded {
+            return None;
+        }
+        let direction = self
+            .order
+            .into_iter()
+            .find(|d| d.checks((x, y)).iter().all(|n| !self.elves.contains(n)))?;
+        let (dx, dy) = direction.step();
+        Some((x + dx, y + dy))
+    }
+
+    /// Plays one round, returns how many elves moved.
+    fn round(&mut self) -> usize {
+        // every target with the elf proposing it, `None` once a second elf wants it too.
+        let mut proposals: HashMap<Pos, Option<Pos>> = HashMap::new();
+        for elf in &self.elves {
+            if let Some(target) = self.proposal(*elf) {
+                proposals
+                    .entry(target)
+                    .and_modify(|from| *from = None)
+                    .or_insert(Some(*elf));
+            }
+        }
+
+        let mut moved = 0;
+        for (target, from) in proposals {
+            if let Some(from) = from {
+                self.elves.remove(&from);
+                self.elves.insert(target);
+                moved += 1;
+            }
+        }
+        self.order.rotate_left(1);
+        self.rounds += 1;
+        viz::emit(|| {
+            Frame::new(Grid::parse(&self.render(), |c| c))
+                .caption(format!("round {}, {moved} elves moved", self.rounds))
+        });
+        moved
+    }
+
+    /// The smallest and largest coordinates of any elf.
+    fn bounds(&self) -> (Pos, Pos) {
+        let xs = self.elves.iter().map(|(x, _)| *x);
+        let ys = self.elves.iter().map(|(_, y)| *y);
+        (
+            (xs.clone().min().unwrap(), ys.clone().min().unwrap()),
+            (xs.max().unwrap(), ys.max().unwrap()),
+        )
+    }
+
+    /// Empty tiles in the smallest rectangle holding every elf.
+    fn empty_ground(&self) -> usize {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize - self.elves.len()
+    }
+
+    /// The smallest rectangle holding every elf, drawn like the puzzle does.
+    fn render(&self) -> String {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds();
+        let mut out = String::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                out.push(if self.elves.contains(&(x, y)) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    let mut grove = Grove::from(input);
+    for _ in 0..10 {
+        grove.round();
+    }
+    Some(grove.empty_ground())
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    let mut grove = Grove::from(input);
+    while grove.round() > 0 {}
+    Some(grove.rounds)
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 23);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_example() {
+        let mut grove = Grove::from(".....\n..##.\n..#..\n.....\n..##.\n.....");
+        assert_eq!(grove.render(), "##\n#.\n..\n##\n");
+
+        assert_eq!(grove.round(), 3);
+        assert_eq!(grove.render(), "##\n..\n#.\n.#\n#.\n");
+        assert_eq!(grove.round(), 5);
+        assert_eq!(grove.render(), ".##.\n#...\n...#\n....\n.#..\n");
+        assert_eq!(grove.round(), 3);
+        assert_eq!(grove.render(), "..#..\n....#\n#....\n....#\n.....\n..#..\n");
+        assert_eq!(grove.round(), 0);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 23);
+        assert_eq!(part_one(&input), Some(110));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 23);
+        assert_eq!(part_two(&input), Some(20));
+    }
+}
@@ -0,0 +1,131 @@
+use advent_of_code::helpers::grid::{Grid, Point};
+use advent_of_code::helpers::math::lcm;
+use pathfinding::prelude::bfs;
+use std::iter;
+
+/// The basin with its walls, blizzards as they are at minute 0.
+#[derive(Debug)]
+struct Valley {
+    grid: Grid<char>,
+    /// Every blizzard is back where it started after this many minutes.
+    period: usize,
+    start: Point,
+    goal: Point,
+}
+
+impl From<&str> for Valley {
+    fn from(input: &str) -> Valley {
+        let grid = Grid::parse(input, |c| c);
+        let opening = |y: usize| {
+            (0..grid.width())
+                .map(|x| (x, y))
+                .find(|pos| grid[*pos] == '.')
+                .unwrap_or_else(|| panic!("no opening in row {y}"))
+        };
+        let (start, goal) = (opening(0), opening(grid.height() - 1));
+        let period = lcm(grid.width() as u64 - 2, grid.height() as u64 - 2) as usize;
+        Valley {
+            grid,
+            period,
+            start,
+            goal,
+        }
+    }
+}
+
+impl Valley {
+    /// Whether a blizzard is at `pos` at minute `time`. Blizzards wrap around inside the
+    /// walls, so instead of moving them this looks for one `time` steps upwind of `pos`.
+    fn blizzard_at(&self, (x, y): Point, time: usize) -> bool {
+        if self.grid[(x, y)] == '#' || y == 0 || y == self.grid.height() - 1 {
+            return false;
+        }
+        let (w, h) = (self.grid.width() - 2, self.grid.height() - 2);
+        let (x, y) = (x - 1, y - 1);
+        let (tx, ty) = (time % w, time % h);
+        let at = |x: usize, y: usize| self.grid[(x + 1, y + 1)];
+        at((x + w - tx) % w, y) == '>'
+            || at((x + tx) % w, y) == '<'
+            || at(x, (y + h - ty) % h) == 'v'
+            || at(x, (y + ty) % h) == '^'
+    }
+
+    fn open(&self, pos: Point, time: usize) -> bool {
+        self.grid[pos] != '#' && !self.blizzard_at(pos, time)
+    }
+
+    /// The minute of arriving at `to` when leaving `from` at minute `time`. Searches
+    /// `(position, minute mod period)` states, the blizzards look the same for all
+    /// minutes sharing a remainder.
+    fn crossing(&self, from: Point, to: Point, time: usize) -> Option<usize> {
+        let path = bfs(
+            &(from, time % self.period),
+            |&(pos, t)| {
+                let t = (t + 1) % self.period;
+                self.grid
+                    .neighbours(pos)
+                    .chain(iter::once(pos))
+                    .filter(move |next| self.open(*next, t))
+                    .map(move |next| (next, t))
+                    .collect::<Vec<_>>()
+            },
+            |&(pos, _)| pos == to,
+        )?;
+        Some(time + path.len() - 1)
+    }
+
+    /// The minute of arriving at the goal after going there, back to the start for the
+    /// snacks and there again.
+    fn round_trip(&self) -> Option<usize> {
+        let there = self.crossing(self.start, self.goal, 0)?;
+        let back = self.crossing(self.goal, self.start, there)?;
+        self.crossing(self.start, self.goal, back)
+    }
+}
+
+pub fn part_one(input: &str) -> Option<usize> {
+    let valley = Valley::from(input);
+    valley.crossing(valley.start, valley.goal, 0)
+}
+
+pub fn part_two(input: &str) -> Option<usize> {
+    Valley::from(input).round_trip()
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 24);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blizzard_at() {
+        let valley = Valley::from("#.#####\n#.....#\n#>....#\n#.....#\n#...v.#\n#.....#\n#####.#");
+        assert_eq!(valley.period, 5);
+        assert_eq!((valley.start, valley.goal), ((1, 0), (5, 6)));
+        assert!(valley.blizzard_at((1, 2), 0));
+        assert!(valley.blizzard_at((4, 4), 0));
+        // both blizzards meet in the same spot after three minutes.
+        assert!(valley.blizzard_at((4, 2), 3));
+        assert!(!valley.blizzard_at((1, 2), 3));
+        assert!(valley.blizzard_at((5, 2), 4));
+        assert!(valley.blizzard_at((1, 2), 5));
+        assert!(valley.blizzard_at((4, 1), 2));
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 24);
+        assert_eq!(part_one(&input), Some(18));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", 24);
+        assert_eq!(part_two(&input), Some(54));
+    }
+}
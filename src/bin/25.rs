@@ -0,0 +1,274 @@
+use std::fmt::{self, Display};
+use std::iter::Sum;
+use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+/// A number in SNAFU notation: base 5 with the digits `=`, `-`, `0`, `1` and `2` worth
+/// -2 to 2. Digits are kept least significant first, without leading zeros, so every
+/// number has exactly one representation and zero has no digits at all.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+struct Snafu {
+    digits: Vec<i8>,
+}
+
+#[derive(Debug, PartialEq)]
+enum SnafuError {
+    Empty,
+    InvalidDigit(char, usize),
+    TooLarge,
+}
+
+impl Display for SnafuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnafuError::Empty => write!(f, "empty SNAFU number"),
+            SnafuError::InvalidDigit(c, pos) => write!(f, "invalid digit '{c}' at position {pos}"),
+            SnafuError::TooLarge => write!(f, "SNAFU number does not fit in an i64"),
+        }
+    }
+}
+
+impl Snafu {
+    fn from_digits(mut digits: Vec<i8>) -> Snafu {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Snafu { digits }
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = SnafuError;
+
+    fn from_str(s: &str) -> Result<Snafu, SnafuError> {
+        if s.is_empty() {
+            return Err(SnafuError::Empty);
+        }
+        let digits = s
+            .chars()
+            .enumerate()
+            .map(|(pos, c)| match c {
+                '=' => Ok(-2),
+                '-' => Ok(-1),
+                '0' => Ok(0),
+                '1' => Ok(1),
+                '2' => Ok(2),
+                _ => Err(SnafuError::InvalidDigit(c, pos)),
+            })
+            .collect::<Result<Vec<i8>, _>>()?;
+        Ok(Snafu::from_digits(digits.into_iter().rev().collect()))
+    }
+}
+
+impl Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for digit in self.digits.iter().rev() {
+            let c = match digit {
+                -2 => '=',
+                -1 => '-',
+                0 => '0',
+                1 => '1',
+                2 => '2',
+                _ => unreachable!("invalid SNAFU digit {digit}"),
+            };
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(value: i64) -> Snafu {
+        // widened so that stepping to the next multiple of 5 cannot overflow near i64::MIN.
+        let mut n = value as i128;
+        let mut digits = vec![];
+        while n != 0 {
+            let digit = match n.rem_euclid(5) {
+                d @ 0..=2 => d,
+                d => d - 5,
+            };
+            digits.push(digit as i8);
+            n = (n - digit) / 5;
+        }
+        Snafu::from_digits(digits)
+    }
+}
+
+impl TryFrom<&Snafu> for i64 {
+    type Error = SnafuError;
+
+    fn try_from(value: &Snafu) -> Result<i64, SnafuError> {
+        // a prefix can be just outside the range of the full number, e.g. for i64::MIN.
+        let n = value.digits.iter().rev().try_fold(0i128, |n, digit| {
+            n.checked_mul(5)
+                .and_then(|n| n.checked_add(*digit as i128))
+                .ok_or(SnafuError::TooLarge)
+        })?;
+        i64::try_from(n).map_err(|_| SnafuError::TooLarge)
+    }
+}
+
+impl Add for &Snafu {
+    type Output = Snafu;
+
+    /// Adds digit by digit, carrying whenever a sum leaves the range -2 to 2.
+    fn add(self, rhs: &Snafu) -> Snafu {
+        let len = self.digits.len().max(rhs.digits.len());
+        let mut digits = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = self.digits.get(i).copied().unwrap_or(0);
+            let b = rhs.digits.get(i).copied().unwrap_or(0);
+            let sum = a + b + carry;
+            let digit;
+            (carry, digit) = match sum {
+                3.. => (1, sum - 5),
+                ..=-3 => (-1, sum + 5),
+                _ => (0, sum),
+            };
+            digits.push(digit);
+        }
+        digits.push(carry);
+        Snafu::from_digits(digits)
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, rhs: Snafu) -> Snafu {
+        &self + &rhs
+    }
+}
+
+impl Neg for Snafu {
+    type Output = Snafu;
+
+    /// The digits are symmetric around zero, so negating one digit at a time never carries.
+    fn neg(self) -> Snafu {
+        Snafu {
+            digits: self.digits.into_iter().map(|d| -d).collect(),
+        }
+    }
+}
+
+impl Sub for Snafu {
+    type Output = Snafu;
+
+    fn sub(self, rhs: Snafu) -> Snafu {
+        self + -rhs
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), Add::add)
+    }
+}
+
+pub fn part_one(input: &str) -> Option<String> {
+    let sum: Snafu = input
+        .lines()
+        .map(|line| {
+            line.parse()
+                .unwrap_or_else(|err| panic!("invalid input: {err}"))
+        })
+        .sum();
+    Some(sum.to_string())
+}
+
+// day 25 has no second puzzle.
+fn main() {
+    let input = &advent_of_code::read_file("inputs", 25);
+    advent_of_code::solve!(1, part_one, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The conversion tables from the puzzle description.
+    const TABLE: [(i64, &str); 26] = [
+        (1, "1"),
+        (2, "2"),
+        (3, "1="),
+        (4, "1-"),
+        (5, "10"),
+        (6, "11"),
+        (7, "12"),
+        (8, "2="),
+        (9, "2-"),
+        (10, "20"),
+        (15, "1=0"),
+        (20, "1-0"),
+        (2022, "1=11-2"),
+        (12345, "1-0---0"),
+        (314159265, "1121-1110-1=0"),
+        (1747, "1=-0-2"),
+        (906, "12111"),
+        (198, "2=0="),
+        (11, "21"),
+        (201, "2=01"),
+        (31, "111"),
+        (1257, "20012"),
+        (32, "112"),
+        (353, "1=-1="),
+        (107, "1-12"),
+        (37, "122"),
+    ];
+
+    #[test]
+    fn test_conversion_table() {
+        for (decimal, snafu) in TABLE {
+            let parsed: Snafu = snafu.parse().unwrap();
+            assert_eq!(i64::try_from(&parsed), Ok(decimal), "{snafu}");
+            assert_eq!(Snafu::from(decimal), parsed, "{decimal}");
+            assert_eq!(parsed.to_string(), snafu);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for n in (-2000..=2000).chain([i64::MIN, i64::MIN + 1, i64::MAX, i64::MAX - 1]) {
+            let snafu = Snafu::from(n);
+            assert_eq!(i64::try_from(&snafu), Ok(n));
+            assert_eq!(snafu.to_string().parse(), Ok(snafu));
+        }
+        assert_eq!(Snafu::from(0).to_string(), "0");
+        assert_eq!("000".parse(), Ok(Snafu::from(0)));
+        assert_eq!("0012".parse(), Ok(Snafu::from(7)));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        for a in -130..=130 {
+            for b in -130..=130 {
+                assert_eq!(Snafu::from(a) + Snafu::from(b), Snafu::from(a + b));
+                assert_eq!(Snafu::from(a) - Snafu::from(b), Snafu::from(a - b));
+            }
+        }
+        // sums are fine beyond i64, only converting them back fails.
+        let max = Snafu::from(i64::MAX);
+        let twice = &max + &max;
+        assert_eq!(i64::try_from(&twice), Err(SnafuError::TooLarge));
+        assert_eq!(twice - max.clone(), max);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<Snafu>(), Err(SnafuError::Empty));
+        assert_eq!(
+            "1=3".parse::<Snafu>(),
+            Err(SnafuError::InvalidDigit('3', 2))
+        );
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", 25);
+        assert_eq!(part_one(&input), Some("2=-1=0".to_string()));
+    }
+}
@@ -2,3 +2,12 @@
  * Use this file if you want to extract helpers from your solutions.
  * Example import from this file: `use advent_of_code::helpers::example_fn;`.
  */
+pub mod cycle;
+pub mod graph;
+pub mod grid;
+pub mod math;
+pub mod ocr;
+pub mod point3;
+pub mod range_set;
+pub mod treap;
+pub mod viz;
@@ -0,0 +1,168 @@
+/*
+ * Cycle detection for simulations that eventually repeat themselves.
+ * `floyd` and `brent` find the cycle of a pure step function, `Detector` finds it
+ * while a simulation runs and extrapolates a value (e.g. a height) to a far away step.
+ * The recorded value can also be anything else, e.g. a counter per actor, and be
+ * extrapolated piece by piece with `Cycle::extrapolate`.
+ */
+use pathfinding::prelude;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states from step `start` on repeat every `length` steps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The earliest step that has the same state as `step`.
+    pub fn reduce(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+
+    /// The value at `step`, assuming it grows by the same amount every time the cycle repeats.
+    /// `value` has to know the values of all steps up to the end of the first repetition.
+    pub fn extrapolate<F>(&self, step: usize, value: F) -> i64
+    where
+        F: Fn(usize) -> i64,
+    {
+        if step <= self.start + self.length {
+            return value(step);
+        }
+        let growth = value(self.start + self.length) - value(self.start);
+        let repetitions = ((step - self.start) / self.length) as i64;
+        value(self.reduce(step)) + repetitions * growth
+    }
+}
+
+pub fn floyd<T, F>(initial: T, step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: Fn(T) -> T,
+{
+    let (length, _, start) = prelude::floyd(initial, step);
+    Cycle { start, length }
+}
+
+pub fn brent<T, F>(initial: T, step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: Fn(T) -> T,
+{
+    let (length, _, start) = prelude::brent(initial, step);
+    Cycle { start, length }
+}
+
+/// Remembers the step at which every state key was first seen, along with a value
+/// that is recorded once per step.
+#[derive(Debug)]
+pub struct Detector<K, V = i64> {
+    seen: HashMap<K, usize>,
+    values: Vec<V>,
+}
+
+impl<K: Eq + Hash, V> Default for Detector<K, V> {
+    fn default() -> Self {
+        Detector {
+            seen: HashMap::new(),
+            values: vec![],
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Detector<K, V> {
+    pub fn new() -> Detector<K, V> {
+        Detector::default()
+    }
+
+    /// Records the state key and value of the next step.
+    /// Returns the cycle as soon as a key is seen for the second time.
+    pub fn record(&mut self, key: K, value: V) -> Option<Cycle> {
+        let step = self.values.len();
+        self.values.push(value);
+        match self.seen.get(&key) {
+            Some(&start) => Some(Cycle {
+                start,
+                length: step - start,
+            }),
+            None => {
+                self.seen.insert(key, step);
+                None
+            }
+        }
+    }
+
+    /// The values recorded so far, one per step.
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+}
+
+impl<K: Eq + Hash> Detector<K, i64> {
+    /// The value at `step`, assuming it grows by the same amount every time the cycle repeats.
+    pub fn extrapolate(&self, cycle: &Cycle, step: usize) -> i64 {
+        cycle.extrapolate(step, |step| self.values[step])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+    fn step(i: u32) -> u32 {
+        if i == 5 {
+            3
+        } else {
+            i + 1
+        }
+    }
+
+    #[test]
+    fn test_floyd_brent() {
+        let expected = Cycle {
+            start: 3,
+            length: 3,
+        };
+        assert_eq!(floyd(0, step), expected);
+        assert_eq!(brent(0, step), expected);
+        assert_eq!(expected.reduce(2), 2);
+        assert_eq!(expected.reduce(10), 4);
+        // a value growing by 2 every step keeps doing so.
+        assert_eq!(expected.extrapolate(10, |step| step as i64 * 2), 20);
+    }
+
+    #[test]
+    fn test_detector() {
+        let mut detector = Detector::new();
+        let mut state = 0;
+        let mut total = 0;
+        let cycle = loop {
+            if let Some(cycle) = detector.record(state, total) {
+                break cycle;
+            }
+            total += state as i64;
+            state = step(state);
+        };
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 3,
+                length: 3
+            }
+        );
+
+        let (mut state, mut total) = (0, 0);
+        for i in 0..=20 {
+            assert_eq!(detector.extrapolate(&cycle, i), total);
+            total += state as i64;
+            state = step(state);
+        }
+    }
+}
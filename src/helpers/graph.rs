@@ -0,0 +1,263 @@
+/*
+ * Shortest-path searches over a `Grid`, built on top of `pathfinding`.
+ * Moves are orthogonal steps; whether a step is allowed is decided by looking at the
+ * values of the cell being left and the cell being entered.
+ * Searches that find a target return the path (including start and target) and its cost.
+ * `floyd_warshall` works on any small graph with numbered nodes instead.
+ */
+use super::grid::{Grid, Point};
+use pathfinding::matrix::Matrix;
+use pathfinding::num_traits::Zero;
+use pathfinding::prelude;
+use std::collections::VecDeque;
+
+fn passable_neighbours<'a, T, P>(
+    grid: &'a Grid<T>,
+    passable: &'a P,
+    pos: Point,
+) -> impl Iterator<Item = Point> + 'a
+where
+    P: Fn(&T, &T) -> bool,
+{
+    grid.neighbours(pos)
+        .filter(move |next| passable(&grid[pos], &grid[*next]))
+}
+
+fn weighted_neighbours<'a, T, C, F>(
+    grid: &'a Grid<T>,
+    cost: &'a F,
+    pos: Point,
+) -> impl Iterator<Item = (Point, C)> + 'a
+where
+    F: Fn(&T, &T) -> Option<C>,
+{
+    grid.neighbours(pos)
+        .filter_map(move |next| cost(&grid[pos], &grid[next]).map(|c| (next, c)))
+}
+
+/// Unweighted search from `start` to the closest position satisfying `success`.
+pub fn bfs<T, P, S>(
+    grid: &Grid<T>,
+    start: Point,
+    passable: P,
+    success: S,
+) -> Option<(Vec<Point>, usize)>
+where
+    P: Fn(&T, &T) -> bool,
+    S: Fn(Point) -> bool,
+{
+    let path = prelude::bfs(
+        &start,
+        |pos| passable_neighbours(grid, &passable, *pos),
+        |pos| success(*pos),
+    )?;
+    let steps = path.len() - 1;
+    Some((path, steps))
+}
+
+/// Like [`bfs`], but starting from whichever of `starts` is closest to a target.
+pub fn bfs_multi<T, P, S>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item = Point>,
+    passable: P,
+    success: S,
+) -> Option<(Vec<Point>, usize)>
+where
+    P: Fn(&T, &T) -> bool,
+    S: Fn(Point) -> bool,
+{
+    // a virtual root (`None`) connected to every start turns this into a single-source search.
+    let starts: Vec<Point> = starts.into_iter().collect();
+    let path = prelude::bfs(
+        &None,
+        |pos: &Option<Point>| match pos {
+            None => starts.iter().copied().map(Some).collect::<Vec<_>>(),
+            Some(pos) => passable_neighbours(grid, &passable, *pos)
+                .map(Some)
+                .collect(),
+        },
+        |pos| pos.is_some_and(&success),
+    )?;
+    let path: Vec<Point> = path.into_iter().flatten().collect();
+    let steps = path.len() - 1;
+    Some((path, steps))
+}
+
+/// Number of steps from the nearest of `starts` to every cell, `None` where unreachable.
+pub fn bfs_distances<T, P>(
+    grid: &Grid<T>,
+    starts: impl IntoIterator<Item = Point>,
+    passable: P,
+) -> Grid<Option<usize>>
+where
+    P: Fn(&T, &T) -> bool,
+{
+    let mut distances = Grid::filled(grid.width(), grid.height(), None);
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if distances[start].is_none() {
+            distances[start] = Some(0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(pos) = queue.pop_front() {
+        let distance = distances[pos].unwrap() + 1;
+        for next in passable_neighbours(grid, &passable, pos) {
+            if distances[next].is_none() {
+                distances[next] = Some(distance);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Weighted search; `cost` returns `None` for steps that are not allowed.
+pub fn dijkstra<T, C, F, S>(
+    grid: &Grid<T>,
+    start: Point,
+    cost: F,
+    success: S,
+) -> Option<(Vec<Point>, C)>
+where
+    C: Zero + Ord + Copy,
+    F: Fn(&T, &T) -> Option<C>,
+    S: Fn(Point) -> bool,
+{
+    prelude::dijkstra(
+        &start,
+        |pos| weighted_neighbours(grid, &cost, *pos),
+        |pos| success(*pos),
+    )
+}
+
+/// Weighted search guided by `heuristic`, which must never overestimate the remaining cost.
+pub fn astar<T, C, F, H, S>(
+    grid: &Grid<T>,
+    start: Point,
+    cost: F,
+    heuristic: H,
+    success: S,
+) -> Option<(Vec<Point>, C)>
+where
+    C: Zero + Ord + Copy,
+    F: Fn(&T, &T) -> Option<C>,
+    H: Fn(Point) -> C,
+    S: Fn(Point) -> bool,
+{
+    prelude::astar(
+        &start,
+        |pos| weighted_neighbours(grid, &cost, *pos),
+        |pos| heuristic(*pos),
+        |pos| success(*pos),
+    )
+}
+
+/// Cheapest costs between all pairs of `size` nodes connected by directed `(from, to, cost)`
+/// edges, `None` where there is no path.
+pub fn floyd_warshall<C, I>(size: usize, edges: I) -> Matrix<Option<C>>
+where
+    C: Zero + Ord + Copy,
+    I: IntoIterator<Item = (usize, usize, C)>,
+{
+    let mut costs = Matrix::new(size, size, None);
+    for node in 0..size {
+        costs[(node, node)] = Some(C::zero());
+    }
+    for (from, to, cost) in edges {
+        if costs[(from, to)].is_none_or(|c| cost < c) {
+            costs[(from, to)] = Some(cost);
+        }
+    }
+    for via in 0..size {
+        for from in 0..size {
+            let Some(first) = costs[(from, via)] else {
+                continue;
+            };
+            for to in 0..size {
+                if let Some(second) = costs[(via, to)] {
+                    let cost = first + second;
+                    if costs[(from, to)].is_none_or(|c| cost < c) {
+                        costs[(from, to)] = Some(cost);
+                    }
+                }
+            }
+        }
+    }
+    costs
+}
+
+pub fn manhattan_distance(a: Point, b: Point) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "..#.\n.##.\n....";
+
+    fn maze() -> Grid<bool> {
+        Grid::parse(MAZE, |c| c == '.')
+    }
+
+    fn open(_: &bool, to: &bool) -> bool {
+        *to
+    }
+
+    #[test]
+    fn test_bfs() {
+        let (path, steps) = bfs(&maze(), (0, 0), open, |p| p == (3, 0)).unwrap();
+        assert_eq!(steps, 7);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(3, 0)));
+        assert_eq!(bfs(&maze(), (0, 0), open, |p| p == (2, 0)), None);
+    }
+
+    #[test]
+    fn test_bfs_multi() {
+        let (path, steps) = bfs_multi(&maze(), [(0, 0), (3, 2)], open, |p| p == (3, 0)).unwrap();
+        assert_eq!(steps, 2);
+        assert_eq!(path, vec![(3, 2), (3, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = bfs_distances(&maze(), [(0, 0)], open);
+        assert_eq!(distances[(0, 0)], Some(0));
+        assert_eq!(distances[(0, 2)], Some(2));
+        assert_eq!(distances[(3, 0)], Some(7));
+        assert_eq!(distances[(2, 0)], None);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let grid = Grid::parse("19\n11", |c| c.to_digit(10).unwrap());
+        let cost = |_: &u32, to: &u32| Some(*to);
+        let (path, cost_sum) = dijkstra(&grid, (0, 0), cost, |p| p == (1, 0)).unwrap();
+        assert_eq!(cost_sum, 9);
+        assert_eq!(path, vec![(0, 0), (1, 0)]);
+
+        let (_, cost_sum) = astar(
+            &grid,
+            (0, 0),
+            cost,
+            |p| manhattan_distance(p, (1, 1)) as u32,
+            |p| p == (1, 1),
+        )
+        .unwrap();
+        assert_eq!(cost_sum, 2);
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let costs = floyd_warshall(4, [(0, 1, 5), (1, 2, 1), (0, 2, 9), (2, 0, 2)]);
+        assert_eq!(costs[(0, 2)], Some(6));
+        assert_eq!(costs[(2, 1)], Some(7));
+        assert_eq!(costs[(1, 1)], Some(0));
+        assert_eq!(costs[(0, 3)], None);
+        assert_eq!(costs[(3, 0)], None);
+    }
+}
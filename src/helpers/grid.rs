@@ -0,0 +1,158 @@
+use std::ops::{Index, IndexMut};
+
+/// `(x, y)` coordinates into a [`Grid`], with `(0, 0)` in the top left corner.
+pub type Point = (usize, usize);
+
+/// A dense, row-major 2D grid.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "cell count must match size");
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parses one row per line, mapping every character to a cell.
+    pub fn parse<F>(input: &str, mut cell: F) -> Grid<T>
+    where
+        F: FnMut(char) -> T,
+    {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = vec![];
+        for line in input.lines() {
+            cells.extend(line.chars().map(&mut cell));
+            width = line.chars().count();
+            height += 1;
+        }
+        Grid::new(width, height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Point) -> bool {
+        pos.0 < self.width && pos.1 < self.height
+    }
+
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        if self.contains(pos) {
+            self.cells.get(pos.1 * self.width + pos.0)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        if self.contains(pos) {
+            self.cells.get_mut(pos.1 * self.width + pos.0)
+        } else {
+            None
+        }
+    }
+
+    /// All cells with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    pub fn find<F>(&self, mut predicate: F) -> Option<Point>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .find_map(|(pos, cell)| if predicate(cell) { Some(pos) } else { None })
+    }
+
+    /// Orthogonal neighbours of `pos` that lie inside the grid (up, left, right, down).
+    pub fn neighbours(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        let (x, y) = pos;
+        [
+            y.checked_sub(1).map(|y| (x, y)),
+            x.checked_sub(1).map(|x| (x, y)),
+            Some((x + 1, y)),
+            Some((x, y + 1)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|pos| self.contains(*pos))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T> {
+        Grid::new(width, height, vec![value; width * height])
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point) -> &T {
+        self.get(pos).expect("position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, pos: Point) -> &mut T {
+        self.get_mut(pos).expect("position out of bounds")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("ab\ncd\nef", |c| c);
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[(1, 0)], 'b');
+        assert_eq!(grid[(0, 2)], 'e');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.find(|c| *c == 'd'), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::filled(3, 3, 0);
+        assert_eq!(
+            grid.neighbours((0, 0)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1)]
+        );
+        assert_eq!(
+            grid.neighbours((1, 1)).collect::<Vec<_>>(),
+            vec![(1, 0), (0, 1), (2, 1), (1, 2)]
+        );
+        assert_eq!(
+            grid.neighbours((2, 2)).collect::<Vec<_>>(),
+            vec![(2, 1), (1, 2)]
+        );
+    }
+}
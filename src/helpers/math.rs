@@ -0,0 +1,308 @@
+/*
+ * Number theory and overflow-checked arithmetic.
+ * `Checked` panics on overflow in release builds too, so solutions that wrap their
+ * numbers in it can be sure their answer has not silently wrapped around.
+ */
+use pathfinding::num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedRem, CheckedSub};
+use std::fmt::{self, Display};
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+/// Answer type for solutions whose result may not fit into 64 bits.
+/// Arbitrary precision with the `bigint` feature enabled, `i128` otherwise.
+#[cfg(feature = "bigint")]
+pub type Answer = num_bigint::BigInt;
+#[cfg(not(feature = "bigint"))]
+pub type Answer = i128;
+
+/// An integer whose arithmetic operators panic instead of overflowing.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Checked<T>(pub T);
+
+macro_rules! impl_checked_op {
+    ($trait:ident, $fn:ident, $checked_trait:ident, $checked_fn:ident, $symbol:literal) => {
+        impl<T: $checked_trait + Display> $trait for Checked<T> {
+            type Output = Checked<T>;
+
+            fn $fn(self, rhs: Checked<T>) -> Checked<T> {
+                match self.0.$checked_fn(&rhs.0) {
+                    Some(result) => Checked(result),
+                    None => panic!("arithmetic overflow: {} {} {}", self.0, $symbol, rhs.0),
+                }
+            }
+        }
+    };
+}
+
+impl_checked_op!(Add, add, CheckedAdd, checked_add, "+");
+impl_checked_op!(Sub, sub, CheckedSub, checked_sub, "-");
+impl_checked_op!(Mul, mul, CheckedMul, checked_mul, "*");
+impl_checked_op!(Div, div, CheckedDiv, checked_div, "/");
+impl_checked_op!(Rem, rem, CheckedRem, checked_rem, "%");
+
+impl<T: Display> Display for Checked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: CheckedAdd + Display + From<u8>> Sum for Checked<T> {
+    fn sum<I: Iterator<Item = Checked<T>>>(iter: I) -> Checked<T> {
+        iter.fold(Checked(T::from(0)), Add::add)
+    }
+}
+
+impl<T: CheckedMul + Display + From<u8>> Product for Checked<T> {
+    fn product<I: Iterator<Item = Checked<T>>>(iter: I) -> Checked<T> {
+        iter.fold(Checked(T::from(1)), Mul::mul)
+    }
+}
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (Checked(a / gcd(a, b)) * Checked(b)).0
+}
+
+/// An exact fraction, always stored in lowest terms with a positive denominator.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub fn new(numerator: i128, denominator: i128) -> Rational {
+        assert_ne!(denominator, 0, "division by zero");
+        let g = extended_gcd(numerator, denominator).0.max(1);
+        let sign = denominator.signum();
+        Rational {
+            numerator: sign * numerator / g,
+            denominator: sign * denominator / g,
+        }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    /// The value as an integer, `None` if it has a fractional part or does not fit.
+    pub fn to_integer(&self) -> Option<i64> {
+        if self.denominator == 1 {
+            i64::try_from(self.numerator).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Rational {
+        Rational::new(value as i128, 1)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Rational {
+        let numerator = Checked(self.numerator) * Checked(rhs.denominator)
+            + Checked(rhs.numerator) * Checked(self.denominator);
+        let denominator = Checked(self.denominator) * Checked(rhs.denominator);
+        Rational::new(numerator.0, denominator.0)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Rational {
+        let numerator = Checked(self.numerator) * Checked(rhs.denominator)
+            - Checked(rhs.numerator) * Checked(self.denominator);
+        let denominator = Checked(self.denominator) * Checked(rhs.denominator);
+        Rational::new(numerator.0, denominator.0)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Rational {
+        let numerator = Checked(self.numerator) * Checked(rhs.numerator);
+        let denominator = Checked(self.denominator) * Checked(rhs.denominator);
+        Rational::new(numerator.0, denominator.0)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Rational) -> Rational {
+        let numerator = Checked(self.numerator) * Checked(rhs.denominator);
+        let denominator = Checked(self.denominator) * Checked(rhs.numerator);
+        Rational::new(numerator.0, denominator.0)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a.abs(), a.signum(), 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// The `x` in `0..modulus` with `a * x ≡ 1 (mod modulus)`, if `a` and `modulus` are coprime.
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a as i128, modulus as i128);
+    if g == 1 {
+        Some(x.rem_euclid(modulus as i128) as i64)
+    } else {
+        None
+    }
+}
+
+/// Chinese remainder theorem: solves `x ≡ residue (mod modulus)` for all `(residue, modulus)`.
+/// Returns the smallest non-negative `x` and the combined modulus, or `None` if the
+/// congruences contradict each other, a modulus is not positive or the combined modulus
+/// does not fit in an `i64`. Moduli do not need to be coprime.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let (mut x, mut m) = (0_i128, 1_i128);
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 {
+            return None;
+        }
+        let (residue, modulus) = (residue as i128, modulus as i128);
+        let (g, p, _) = extended_gcd(m, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = modulus / g;
+        // both factors are below `step`, so their product fits even for moduli near 2^63.
+        let t = (diff / g).rem_euclid(step) * p.rem_euclid(step) % step;
+        x = m.checked_mul(t)?.checked_add(x)?;
+        m = m.checked_mul(step)?;
+        x = x.rem_euclid(m);
+    }
+    Some((i64::try_from(x).ok()?, i64::try_from(m).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(Checked(3_u64) + Checked(4), Checked(7));
+        assert_eq!(Checked(3_u64) * Checked(4) % Checked(5), Checked(2));
+        assert_eq!(
+            [2_u64, 3, 4]
+                .into_iter()
+                .map(Checked)
+                .product::<Checked<_>>(),
+            Checked(24)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn test_checked_overflow() {
+        let _ = Checked(u64::MAX) * Checked(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn test_checked_underflow() {
+        let _ = Checked(1_u32) - Checked(2);
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!([23, 19, 13, 17].into_iter().fold(1, lcm), 96577);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(1, 0)]), None);
+        assert_eq!(crt(&[(1, -3)]), None);
+    }
+
+    #[test]
+    fn test_crt_overflow() {
+        // the combined modulus fits in an i128 but not in an i64.
+        assert_eq!(crt(&[(1, (1 << 62) + 1), (2, (1 << 62) + 3)]), None);
+        // here it does not even fit in an i128.
+        let huge = [(1, (1 << 62) + 1), (2, (1 << 62) + 3), (3, (1 << 62) + 5)];
+        assert_eq!(crt(&huge), None);
+        assert_eq!(crt(&[(5, i64::MAX), (0, 1)]), Some((5, i64::MAX)));
+    }
+
+    #[test]
+    fn test_rational() {
+        let third = Rational::new(1, 3);
+        assert_eq!(third + third + third, Rational::from(1));
+        assert_eq!(Rational::new(4, -6), Rational::new(-2, 3));
+        assert_eq!(Rational::from(7) / Rational::from(2), Rational::new(7, 2));
+        assert_eq!((Rational::from(7) / Rational::from(2)).to_integer(), None);
+        assert_eq!(
+            (Rational::new(3, 4) - Rational::new(1, 4)).to_string(),
+            "1/2"
+        );
+        assert_eq!(
+            (Rational::from(6) * Rational::new(1, 3)).to_integer(),
+            Some(2)
+        );
+        assert_eq!(Rational::new(0, -5), Rational::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn test_rational_division_by_zero() {
+        let _ = Rational::from(1) / Rational::from(0);
+    }
+
+    #[test]
+    fn test_answer() {
+        let answer = Answer::from(811589153_i64) * Answer::from(811589153_i64) * Answer::from(100);
+        assert_eq!(answer.to_string(), "65867695326725740900");
+    }
+}
@@ -0,0 +1,213 @@
+/*
+ * Reads the block letters some puzzles draw instead of printing an answer.
+ * Supports the 4x6 font (e.g. 2022 day 10) and the 6x10 font (e.g. 2018 day 10).
+ */
+use super::grid::Grid;
+
+struct Font {
+    width: usize,
+    height: usize,
+    spacing: usize,
+    glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+    width: 4,
+    height: 6,
+    spacing: 1,
+    glyphs: &[
+        ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+        ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+        ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+        ('E', "####\n#...\n###.\n#...\n#...\n####"),
+        ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+        ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+        ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+        ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+        ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+        ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+        ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+        ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+        ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+        ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+        ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+        ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+        // the only glyph that uses the spacing column as well.
+        ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+        ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+    ],
+};
+
+const LARGE: Font = Font {
+    width: 6,
+    height: 10,
+    spacing: 2,
+    glyphs: &[
+        (
+            'A',
+            "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'B',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+        ),
+        (
+            'C',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+        ),
+        (
+            'E',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'F',
+            "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'G',
+            ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+        ),
+        (
+            'H',
+            "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+        ),
+        (
+            'J',
+            "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+        ),
+        (
+            'K',
+            "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+        ),
+        (
+            'L',
+            "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+        ),
+        (
+            'N',
+            "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+        ),
+        (
+            'P',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+        ),
+        (
+            'R',
+            "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+        ),
+        (
+            'X',
+            "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+        ),
+        (
+            'Z',
+            "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+        ),
+    ],
+};
+
+/// The glyph `width` columns wide starting at column `x`, in the `#`/`.` notation of the fonts.
+/// Columns outside of the image count as unlit.
+fn glyph_at(image: &Grid<bool>, x: usize, width: usize) -> String {
+    (0..image.height())
+        .map(|y| {
+            (x..x + width)
+                .map(|x| match image.get((x, y)) {
+                    Some(true) => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `pattern` with every row padded to `width` columns with unlit cells.
+fn padded(pattern: &str, width: usize) -> String {
+    pattern
+        .lines()
+        .map(|row| format!("{row:.<width$}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads the letters drawn with lit cells in `image`. The font is picked by the image height.
+/// Letters sit in fixed cells of glyph width plus spacing, the first one starting at column 0.
+/// Returns `None` if the image does not match a known font or contains an unknown glyph.
+pub fn recognize(image: &Grid<bool>) -> Option<String> {
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.height == image.height())?;
+    let cell = font.width + font.spacing;
+    let is_lit_column = |x: usize| (0..image.height()).any(|y| image[(x, y)]);
+    let last = (0..image.width()).rev().find(|x| is_lit_column(*x))?;
+
+    (0..=last)
+        .step_by(cell)
+        .map(|x| {
+            let glyph = glyph_at(image, x, cell);
+            font.glyphs
+                .iter()
+                .find(|(_, pattern)| padded(pattern, cell) == glyph)
+                .map(|(letter, _)| *letter)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(text: &str) -> Grid<bool> {
+        Grid::parse(text, |c| c == '#')
+    }
+
+    #[test]
+    fn test_small_font() {
+        let text = "\
+####.#..#.####.####.####.#..#..##..####.
+#....#..#....#.#.......#.#..#.#..#....#.
+###..####...#..###....#..####.#......#..
+#....#..#..#...#.....#...#..#.#.....#...
+#....#..#.#....#....#....#..#.#..#.#....
+####.#..#.####.#....####.#..#..##..####.";
+        assert_eq!(recognize(&image(text)), Some(String::from("EHZFZHCZ")));
+    }
+
+    #[test]
+    fn test_unlit_first_column() {
+        // `I` starts with an unlit column and `Y` runs into the spacing column.
+        let text = "\
+.###.#...#.##...###.
+..#..#...##..#...#..
+..#...#.#.#..#...#..
+..#....#..####...#..
+..#....#..#..#...#..
+.###...#..#..#..###.";
+        assert_eq!(recognize(&image(text)), Some(String::from("IYAI")));
+    }
+
+    #[test]
+    fn test_large_font() {
+        let text = "\
+#....#.....###
+#....#......#.
+#....#......#.
+#....#......#.
+######......#.
+#....#......#.
+#....#......#.
+#....#..#...#.
+#....#..#...#.
+#....#...###..";
+        assert_eq!(recognize(&image(text)), Some(String::from("HJ")));
+    }
+
+    #[test]
+    fn test_unknown() {
+        let text = "##..\n##..\n....\n....\n....\n....";
+        assert_eq!(recognize(&image(text)), None);
+        assert_eq!(recognize(&Grid::filled(40, 6, false)), None);
+        assert_eq!(recognize(&Grid::filled(40, 7, true)), None);
+    }
+}
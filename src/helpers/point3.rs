@@ -0,0 +1,111 @@
+use std::ops::{Add, Sub};
+
+/// An integer position in 3D space.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Point3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// The six unit steps along the axes.
+pub const DIRECTIONS: [Point3; 6] = [
+    Point3::new(1, 0, 0),
+    Point3::new(-1, 0, 0),
+    Point3::new(0, 1, 0),
+    Point3::new(0, -1, 0),
+    Point3::new(0, 0, 1),
+    Point3::new(0, 0, -1),
+];
+
+impl Point3 {
+    pub const fn new(x: i32, y: i32, z: i32) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    /// The six positions sharing a face with this one.
+    pub fn neighbours(self) -> impl Iterator<Item = Point3> {
+        DIRECTIONS.into_iter().map(move |d| self + d)
+    }
+
+    pub fn manhattan_distance(self, other: Point3) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+
+    /// Whether the point lies in the box spanned by `min` and `max`, both inclusive.
+    pub fn within(self, min: Point3, max: Point3) -> bool {
+        (min.x..=max.x).contains(&self.x)
+            && (min.y..=max.y).contains(&self.y)
+            && (min.z..=max.z).contains(&self.z)
+    }
+}
+
+/// The smallest and largest coordinates on every axis, `None` for no points.
+pub fn bounds<I>(points: I) -> Option<(Point3, Point3)>
+where
+    I: IntoIterator<Item = Point3>,
+{
+    points.into_iter().fold(None, |bounds, p| {
+        let (min, max) = bounds.unwrap_or((p, p));
+        Some((
+            Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+            Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+        ))
+    })
+}
+
+impl Add for Point3 {
+    type Output = Point3;
+
+    fn add(self, rhs: Point3) -> Point3 {
+        Point3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Point3;
+
+    fn sub(self, rhs: Point3) -> Point3 {
+        Point3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+/// Parses `x,y,z`.
+impl From<&str> for Point3 {
+    fn from(value: &str) -> Point3 {
+        let coordinates: Vec<i32> = value
+            .split(',')
+            .map(|c| c.trim().parse().unwrap())
+            .collect();
+        match coordinates[..] {
+            [x, y, z] => Point3::new(x, y, z),
+            _ => panic!("invalid point: {value}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point3() {
+        let p = Point3::from("1,-2,3");
+        assert_eq!(p, Point3::new(1, -2, 3));
+        assert_eq!(p + Point3::new(1, 1, 1) - p, Point3::new(1, 1, 1));
+        assert_eq!(p.manhattan_distance(Point3::default()), 6);
+        assert_eq!(p.neighbours().count(), 6);
+        assert!(p.neighbours().all(|n| n.manhattan_distance(p) == 1));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let points = [Point3::new(1, 5, -1), Point3::new(3, 2, 0)];
+        let (min, max) = bounds(points).unwrap();
+        assert_eq!(min, Point3::new(1, 2, -1));
+        assert_eq!(max, Point3::new(3, 5, 0));
+        assert!(Point3::new(2, 2, 0).within(min, max));
+        assert!(!Point3::new(0, 2, 0).within(min, max));
+        assert_eq!(bounds([]), None);
+    }
+}
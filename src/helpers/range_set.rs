@@ -0,0 +1,178 @@
+use std::ops::{Range, RangeInclusive};
+
+/// A set of integers, stored as sorted, non-overlapping, non-adjacent half-open ranges.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl RangeSet {
+    pub fn new() -> RangeSet {
+        RangeSet::default()
+    }
+
+    /// Sorts and merges arbitrary ranges into canonical form.
+    fn normalized(mut ranges: Vec<Range<i64>>) -> RangeSet {
+        ranges.retain(|r| !r.is_empty());
+        ranges.sort_unstable_by_key(|r| r.start);
+
+        let mut merged: Vec<Range<i64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        RangeSet { ranges: merged }
+    }
+
+    fn single(range: Range<i64>) -> RangeSet {
+        if range.is_empty() {
+            RangeSet::new()
+        } else {
+            RangeSet {
+                ranges: Vec::from([range]),
+            }
+        }
+    }
+
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn insert(&mut self, range: impl Into<RangeSet>) {
+        *self = self.union(&range.into());
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        RangeSet::normalized(self.ranges.iter().chain(&other.ranges).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut result = vec![];
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(ra), Some(rb)) = (a.peek(), b.peek()) {
+            let start = ra.start.max(rb.start);
+            let end = ra.end.min(rb.end);
+            if start < end {
+                result.push(start..end);
+            }
+            if ra.end < rb.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        RangeSet { ranges: result }
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut result = vec![];
+        let mut others = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut start = range.start;
+            while let Some(cut) = others.peek() {
+                if cut.end <= start {
+                    others.next();
+                    continue;
+                }
+                if cut.start >= range.end {
+                    break;
+                }
+                if cut.start > start {
+                    result.push(start..cut.start);
+                }
+                start = cut.end;
+                if cut.end > range.end {
+                    break;
+                }
+                others.next();
+            }
+            if start < range.end {
+                result.push(start..range.end);
+            }
+        }
+        RangeSet { ranges: result }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(i).is_some_and(|r| r.contains(&value))
+    }
+
+    pub fn is_superset(&self, other: &RangeSet) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| (r.end - r.start) as u64).sum()
+    }
+}
+
+impl<T: Into<i64>> From<Range<T>> for RangeSet {
+    fn from(range: Range<T>) -> RangeSet {
+        RangeSet::single(range.start.into()..range.end.into())
+    }
+}
+
+impl<T: Into<i64>> From<RangeInclusive<T>> for RangeSet {
+    fn from(range: RangeInclusive<T>) -> RangeSet {
+        let (start, end) = range.into_inner();
+        RangeSet::single(start.into()..(end.into() + 1))
+    }
+}
+
+impl<R: Into<RangeSet>> FromIterator<R> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> RangeSet {
+        RangeSet::normalized(iter.into_iter().flat_map(|r| r.into().ranges).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let set: RangeSet = [5..8, 0..2, 2..3, 7..10, 12..12].into_iter().collect();
+        assert_eq!(set.ranges(), &[0..3, 5..10]);
+        assert_eq!(set.len(), 8);
+        assert!(set.contains(2));
+        assert!(!set.contains(3));
+        assert!(set.contains(9));
+        assert!(!set.contains(10));
+        assert!(RangeSet::from(4..=4).contains(4));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: RangeSet = [0..10, 20..30].into_iter().collect();
+        let b = RangeSet::from(5..25).union(&RangeSet::from(28..=28));
+
+        assert_eq!(a.union(&b), RangeSet::from(0..30));
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25, 28..29]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..28, 29..30]);
+        assert_eq!(b.difference(&a), RangeSet::from(10..20));
+        assert!(a.is_superset(&RangeSet::from(2..=8)));
+        assert!(!a.is_superset(&b));
+        assert!(a.is_superset(&RangeSet::new()));
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set = RangeSet::new();
+        set.insert(-2..=2);
+        set.insert(10..12);
+        set.insert(3..10);
+        assert_eq!(set, RangeSet::from(-2..12));
+        assert_eq!(set.len(), 14);
+    }
+}
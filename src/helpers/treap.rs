@@ -0,0 +1,305 @@
+/*
+ * An implicit treap: a sequence with O(log n) positional insert and move, where every element
+ * keeps a stable handle that can be asked for its current index.
+ * Nodes live in an arena and know their parent, which is what makes `rank` possible.
+ */
+
+/// Identifies an element of an [`ImplicitTreap`] for as long as the treap lives.
+pub type Handle = usize;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    left: Option<Handle>,
+    right: Option<Handle>,
+    parent: Option<Handle>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ImplicitTreap<T> {
+    nodes: Vec<Node<T>>,
+    root: Option<Handle>,
+    seed: u64,
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        ImplicitTreap {
+            nodes: vec![],
+            root: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> ImplicitTreap<T> {
+        ImplicitTreap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// xorshift, fixed seed: the shape of the tree is the same on every run.
+    fn next_priority(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+
+    fn size(&self, node: Option<Handle>) -> usize {
+        node.map_or(0, |n| self.nodes[n].size)
+    }
+
+    fn update(&mut self, node: Handle) {
+        let (left, right) = (self.nodes[node].left, self.nodes[node].right);
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn merge(&mut self, a: Option<Handle>, b: Option<Handle>) -> Option<Handle> {
+        match (a, b) {
+            (None, tree) | (tree, None) => tree,
+            (Some(a), Some(b)) => {
+                if self.nodes[a].priority > self.nodes[b].priority {
+                    let right = self.merge(self.nodes[a].right, Some(b));
+                    self.nodes[a].right = right;
+                    self.update(a);
+                    Some(a)
+                } else {
+                    let left = self.merge(Some(a), self.nodes[b].left);
+                    self.nodes[b].left = left;
+                    self.update(b);
+                    Some(b)
+                }
+            }
+        }
+    }
+
+    /// Splits into the first `index` elements and the rest.
+    fn split(&mut self, node: Option<Handle>, index: usize) -> (Option<Handle>, Option<Handle>) {
+        let Some(node) = node else {
+            return (None, None);
+        };
+        let left_size = self.size(self.nodes[node].left);
+        if index <= left_size {
+            let (a, b) = self.split(self.nodes[node].left, index);
+            self.nodes[node].left = b;
+            self.update(node);
+            self.detach_root(a);
+            (a, Some(node))
+        } else {
+            let (a, b) = self.split(self.nodes[node].right, index - left_size - 1);
+            self.nodes[node].right = a;
+            self.update(node);
+            self.detach_root(b);
+            (Some(node), b)
+        }
+    }
+
+    fn detach_root(&mut self, node: Option<Handle>) {
+        if let Some(node) = node {
+            self.nodes[node].parent = None;
+        }
+    }
+
+    /// Makes `child` the left or right child of `parent`, or the root if there is no parent.
+    fn set_child(&mut self, parent: Option<Handle>, left: bool, child: Option<Handle>) {
+        match parent {
+            None => self.root = child,
+            Some(parent) if left => self.nodes[parent].left = child,
+            Some(parent) => self.nodes[parent].right = child,
+        }
+        if let Some(child) = child {
+            self.nodes[child].parent = parent;
+        }
+    }
+
+    /// Puts a detached node at `index`: walks down while the nodes on the way have a higher
+    /// priority, then splits the subtree below that point around the node.
+    fn link(&mut self, handle: Handle, mut index: usize) {
+        let (mut parent, mut left) = (None, false);
+        let mut node = self.root;
+        while let Some(n) = node.filter(|n| self.nodes[*n].priority > self.nodes[handle].priority) {
+            self.nodes[n].size += 1;
+            let left_size = self.size(self.nodes[n].left);
+            parent = Some(n);
+            left = index <= left_size;
+            if left {
+                node = self.nodes[n].left;
+            } else {
+                index -= left_size + 1;
+                node = self.nodes[n].right;
+            }
+        }
+        let (a, b) = self.split(node, index);
+        self.nodes[handle].left = a;
+        self.nodes[handle].right = b;
+        self.update(handle);
+        self.set_child(parent, left, Some(handle));
+    }
+
+    /// Takes a node out of the tree by merging its children into its place.
+    fn unlink(&mut self, handle: Handle) {
+        let Node {
+            left: a,
+            right: b,
+            parent,
+            ..
+        } = self.nodes[handle];
+        let is_left = parent.is_some_and(|p| self.nodes[p].left == Some(handle));
+        let merged = self.merge(a, b);
+        self.set_child(parent, is_left, merged);
+
+        let mut ancestor = parent;
+        while let Some(n) = ancestor {
+            self.nodes[n].size -= 1;
+            ancestor = self.nodes[n].parent;
+        }
+        let node = &mut self.nodes[handle];
+        (node.left, node.right, node.parent, node.size) = (None, None, None, 1);
+    }
+
+    /// Inserts `value` so that it ends up at `index`.
+    pub fn insert(&mut self, index: usize, value: T) -> Handle {
+        assert!(index <= self.len(), "index out of bounds");
+        let handle = self.nodes.len();
+        let priority = self.next_priority();
+        self.nodes.push(Node {
+            value,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+            parent: None,
+        });
+        self.link(handle, index);
+        handle
+    }
+
+    pub fn push(&mut self, value: T) -> Handle {
+        self.insert(self.len(), value)
+    }
+
+    /// The current index of the element behind `handle`.
+    pub fn rank(&self, handle: Handle) -> usize {
+        let mut rank = self.size(self.nodes[handle].left);
+        let mut node = handle;
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                rank += self.size(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+        rank
+    }
+
+    /// Takes the element out of the sequence and puts it back in at `index`,
+    /// counted without the element itself.
+    pub fn move_to(&mut self, handle: Handle, index: usize) {
+        self.unlink(handle);
+        assert!(index <= self.len(), "index out of bounds");
+        self.link(handle, index);
+    }
+
+    pub fn get(&self, handle: Handle) -> &T {
+        &self.nodes[handle].value
+    }
+
+    /// The handle of the element at `index`.
+    pub fn nth(&self, mut index: usize) -> Option<Handle> {
+        let mut node = self.root?;
+        loop {
+            let left_size = self.size(self.nodes[node].left);
+            if index < left_size {
+                node = self.nodes[node].left?;
+            } else if index == left_size {
+                return Some(node);
+            } else {
+                index -= left_size + 1;
+                node = self.nodes[node].right?;
+            }
+        }
+    }
+
+    /// The elements in sequence order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        let mut stack = vec![];
+        let mut next = self.root;
+        std::iter::from_fn(move || {
+            while let Some(node) = next {
+                stack.push(node);
+                next = self.nodes[node].left;
+            }
+            let node = stack.pop()?;
+            next = self.nodes[node].right;
+            Some(&self.nodes[node].value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(treap: &ImplicitTreap<char>) -> String {
+        treap.iter().collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut treap = ImplicitTreap::new();
+        let c = treap.push('c');
+        let a = treap.insert(0, 'a');
+        let b = treap.insert(1, 'b');
+        let d = treap.push('d');
+        assert_eq!(values(&treap), "abcd");
+        assert_eq!(treap.len(), 4);
+        assert_eq!([a, b, c, d].map(|h| treap.rank(h)), [0, 1, 2, 3]);
+        assert_eq!(treap.nth(2).map(|h| *treap.get(h)), Some('c'));
+        assert_eq!(treap.nth(4), None);
+    }
+
+    #[test]
+    fn test_move_to() {
+        let mut treap = ImplicitTreap::new();
+        let handles: Vec<_> = "abcde".chars().map(|c| treap.push(c)).collect();
+        treap.move_to(handles[0], 4);
+        assert_eq!(values(&treap), "bcdea");
+        treap.move_to(handles[3], 0);
+        assert_eq!(values(&treap), "dbcea");
+        treap.move_to(handles[2], 2);
+        assert_eq!(values(&treap), "dbcea");
+        assert_eq!(treap.rank(handles[0]), 4);
+        assert_eq!(treap.rank(handles[3]), 0);
+    }
+
+    #[test]
+    fn test_against_vec() {
+        let mut treap = ImplicitTreap::new();
+        let mut vec: Vec<usize> = vec![];
+        let handles: Vec<_> = (0..200).map(|i| treap.push(i)).collect();
+        vec.extend(0..200);
+
+        for i in 0..1000 {
+            let value = (i * 7919) % 200;
+            let src = vec.iter().position(|v| *v == value).unwrap();
+            assert_eq!(treap.rank(handles[value]), src);
+            let dst = (i * 104729) % 200;
+            vec.remove(src);
+            vec.insert(dst.min(vec.len()), value);
+            treap.move_to(handles[value], dst.min(199));
+        }
+        assert_eq!(treap.iter().copied().collect::<Vec<_>>(), vec);
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * Terminal animations for simulations.
+ * Solvers pass a closure building a `Frame` to `emit`. Frames are only built and drawn when
+ * the solution is run with `--visualize` (e.g. `cargo solve 14 -- --visualize --fps 60`);
+ * otherwise `emit` returns right away, so benchmark timings are unaffected.
+ * With visualization enabled, timings include rendering and are meaningless.
+ */
+use super::grid::{Grid, Point};
+use crate::{ANSI_BOLD, ANSI_RESET};
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+const ANSI_HIGHLIGHT: &str = "\x1b[7m";
+const ANSI_CLEAR: &str = "\x1b[H\x1b[2J";
+const DEFAULT_FPS: u32 = 30;
+
+struct Settings {
+    frame_delay: Duration,
+}
+
+fn settings() -> Option<&'static Settings> {
+    static SETTINGS: OnceLock<Option<Settings>> = OnceLock::new();
+    SETTINGS
+        .get_or_init(|| {
+            let mut args = pico_args::Arguments::from_env();
+            if !args.contains("--visualize") {
+                return None;
+            }
+            let fps: u32 = args
+                .opt_value_from_str("--fps")
+                .ok()
+                .flatten()
+                .unwrap_or(DEFAULT_FPS);
+            Some(Settings {
+                frame_delay: Duration::from_secs(1) / fps.max(1),
+            })
+        })
+        .as_ref()
+}
+
+/// Whether the current run was started with `--visualize`.
+pub fn enabled() -> bool {
+    settings().is_some()
+}
+
+/// Draws the frame built by `frame` and waits for the next one, if visualization is enabled.
+pub fn emit<F>(frame: F)
+where
+    F: FnOnce() -> Frame,
+{
+    if let Some(settings) = settings() {
+        let mut stdout = std::io::stdout().lock();
+        write!(stdout, "{}{}", ANSI_CLEAR, frame().render()).unwrap();
+        stdout.flush().unwrap();
+        thread::sleep(settings.frame_delay);
+    }
+}
+
+/// A picture of a simulation state: characters on a grid, some of them highlighted.
+/// Positions passed to a frame are in the simulation's coordinates, `origin` is the
+/// coordinate of the top left cell.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    grid: Grid<char>,
+    origin: (i64, i64),
+    highlights: HashSet<Point>,
+    caption: Option<String>,
+}
+
+impl Frame {
+    pub fn new(grid: Grid<char>) -> Frame {
+        Frame {
+            grid,
+            origin: (0, 0),
+            highlights: HashSet::new(),
+            caption: None,
+        }
+    }
+
+    /// A frame just large enough to hold all `cells`, filled with `background` elsewhere.
+    pub fn sparse<I>(cells: I, background: char) -> Frame
+    where
+        I: IntoIterator<Item = ((i64, i64), char)>,
+    {
+        let cells: Vec<_> = cells.into_iter().collect();
+        if cells.is_empty() {
+            return Frame::new(Grid::filled(0, 0, background));
+        }
+        let min_x = cells.iter().map(|((x, _), _)| *x).min().unwrap();
+        let max_x = cells.iter().map(|((x, _), _)| *x).max().unwrap();
+        let min_y = cells.iter().map(|((_, y), _)| *y).min().unwrap();
+        let max_y = cells.iter().map(|((_, y), _)| *y).max().unwrap();
+
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        let mut grid = Grid::filled(width, height, background);
+        for ((x, y), c) in cells {
+            grid[((x - min_x) as usize, (y - min_y) as usize)] = c;
+        }
+
+        let mut frame = Frame::new(grid);
+        frame.origin = (min_x, min_y);
+        frame
+    }
+
+    fn to_point(&self, pos: (i64, i64)) -> Option<Point> {
+        let x = usize::try_from(pos.0 - self.origin.0).ok()?;
+        let y = usize::try_from(pos.1 - self.origin.1).ok()?;
+        Some((x, y)).filter(|p| self.grid.contains(*p))
+    }
+
+    /// Highlights the cell at `pos`, positions outside of the frame are ignored.
+    pub fn highlight(mut self, pos: (i64, i64)) -> Frame {
+        if let Some(point) = self.to_point(pos) {
+            self.highlights.insert(point);
+        }
+        self
+    }
+
+    pub fn caption(mut self, caption: impl Into<String>) -> Frame {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// The frame as text, highlighted cells wrapped in ANSI escape sequences.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        if let Some(caption) = &self.caption {
+            out.push_str(&format!("{}{}{}\n", ANSI_BOLD, caption, ANSI_RESET));
+        }
+        for y in 0..self.grid.height() {
+            for x in 0..self.grid.width() {
+                let c = self.grid[(x, y)];
+                if self.highlights.contains(&(x, y)) {
+                    out.push_str(&format!("{}{}{}", ANSI_HIGHLIGHT, c, ANSI_RESET));
+                } else {
+                    out.push(c);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let frame = Frame::new(Grid::parse("ab\ncd", |c| c)).highlight((1, 0));
+        assert_eq!(
+            frame.render(),
+            format!("a{}b{}\ncd\n", ANSI_HIGHLIGHT, ANSI_RESET)
+        );
+    }
+
+    #[test]
+    fn test_sparse() {
+        let frame = Frame::sparse([((-1, -1), 'H'), ((1, 0), 'T')], '.')
+            .highlight((-1, -1))
+            .highlight((5, 5))
+            .caption("step 1");
+        assert_eq!(
+            frame.render(),
+            format!(
+                "{}step 1{}\n{}H{}..\n..T\n",
+                ANSI_BOLD, ANSI_RESET, ANSI_HIGHLIGHT, ANSI_RESET
+            )
+        );
+    }
+}